@@ -0,0 +1,123 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::ifdformat::NumArray;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Canon `MakerNote` IFD
+pub(crate) fn canon_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0001 =>
+        (ExifTag::CanonCameraSettings, "none",
+        IfdFormat::U16, 1, 0xffff, canon_camera_settings),
+
+        0x0002 =>
+        (ExifTag::CanonFocalLength, "none",
+        IfdFormat::U16, 4, 4, canon_focal_length),
+
+        0x0004 =>
+        (ExifTag::CanonShotInfo, "none",
+        IfdFormat::U16, 1, 0xffff, strpass),
+
+        0x0006 =>
+        (ExifTag::CanonImageType, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0007 =>
+        (ExifTag::CanonFirmwareVersion, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0008 =>
+        (ExifTag::CanonFileNumber, "none",
+        IfdFormat::U32, 1, 1, canon_file_number),
+
+        0x0009 =>
+        (ExifTag::CanonOwnerName, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x000c =>
+        (ExifTag::CanonSerialNumber, "none",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0x0010 =>
+        (ExifTag::CanonModelID, "none",
+        IfdFormat::U32, 1, 1, canon_model_id),
+
+        0x0095 =>
+        (ExifTag::CanonLensModel, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0096 =>
+        (ExifTag::CanonInternalSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn canon_focus_mode(mode: u16) -> Cow<'static, str> {
+    match mode {
+        0 => "One-shot AF",
+        1 => "AI Servo AF",
+        2 => "AI Focus AF",
+        3 | 6 => "Manual Focus",
+        4 => "Single",
+        5 => "Continuous",
+        16 => "Pan Focus",
+        256 => "One-shot AF (Live View)",
+        257 => "AI Servo AF (Live View)",
+        258 => "AI Focus AF (Live View)",
+        512 => "Movie Snap Focus",
+        519 => "Movie Servo AF",
+        n => return format!("Unknown ({n})").into(),
+    }.into()
+}
+
+/// Camera settings is an array of values. The first element is the size of the array in bytes.
+fn canon_camera_settings(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            let mut out = format!("Focus mode: {}", canon_focus_mode(*v.get(7)?));
+            if let Some(&lens_type) = v.get(22) {
+                out.push_str(&format!(", lens type: {lens_type}"));
+            }
+            if let (Some(&max), Some(&min), Some(&units)) = (v.get(23), v.get(24), v.get(25)) {
+                if units > 0 && min > 0 {
+                    let (min, max) = (f64::from(min) / f64::from(units), f64::from(max) / f64::from(units));
+                    if min == max {
+                        out.push_str(&format!(", focal length: {min} mm"));
+                    } else {
+                        out.push_str(&format!(", focal length: {min}-{max} mm"));
+                    }
+                }
+            }
+            Some(out.into())
+        },
+        _ => None,
+    }
+}
+
+fn canon_focal_length(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => Some(match v.get(1) {
+            Some(&focal_length) if focal_length > 0 => format!("{focal_length} (focal units)"),
+            _ => NumArray::new(v).to_string(),
+        }.into()),
+        _ => None,
+    }
+}
+
+/// File number is printed by the camera as directory-file
+fn canon_file_number(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U32(ref v) => {
+            let n = v.first()?;
+            Some(format!("{}-{:04}", n / 10000, n % 10000).into())
+        },
+        _ => None,
+    }
+}
+
+fn canon_model_id(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U32(ref v) => Some(format!("{:#010x}", v.first()?).into()),
+        _ => None,
+    }
+}
@@ -2,7 +2,7 @@ use super::exifreadable::*;
 use super::types::*;
 use std::borrow::Cow;
 
-type ReadableFn = fn(u16, &TagValue) -> Option<Cow<'static, str>>;
+pub(crate) type ReadableFn = fn(u16, &TagValue) -> Option<Cow<'static, str>>;
 
 /// Convert a numeric tag into `ExifTag` enumeration, and yields information about the tag. This information
 /// is used by the main body of the parser to sanity-check the tags found in image
@@ -60,9 +60,24 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         (ExifTag::ReferenceBlackWhite, "RGB or YCbCr",
         IfdFormat::URational, 6, 6, rational_values),
 
+        0x013b =>
+        (ExifTag::Artist, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
         0x8298 =>
         (ExifTag::Copyright, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
 
+        0x02bc =>
+        (ExifTag::XMP, "none",
+        IfdFormat::U8, 1, i32::MAX, bytes_as_blob),
+
+        0x8773 =>
+        (ExifTag::InterColorProfile, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+        0x014a =>
+        (ExifTag::SubIFDs, "byte offset",
+        IfdFormat::U32, 1, i32::MAX, strpass),
+
         0x8769 =>
         (ExifTag::ExifOffset, "byte offset",
         IfdFormat::U32, 1, 1, strpass),
@@ -71,6 +86,18 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         (ExifTag::GPSOffset, "byte offset",
         IfdFormat::U32, 1, 1, strpass),
 
+        0x0201 =>
+        (ExifTag::JPEGInterchangeFormat, "byte offset",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0x0202 =>
+        (ExifTag::JPEGInterchangeFormatLength, "bytes",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0xa005 =>
+        (ExifTag::InteropOffset, "byte offset",
+        IfdFormat::U32, 1, 1, strpass),
+
         0x829a =>
         (ExifTag::ExposureTime, "s",
         IfdFormat::URational, 1, 1, exposure_time),
@@ -258,6 +285,14 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         (ExifTag::LensSpecification, "none",
         IfdFormat::URational, 4, 4, lens_spec),
 
+        0xa430 =>
+        (ExifTag::CameraOwnerName, "none",
+        IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0xa431 =>
+        (ExifTag::BodySerialNumber, "none",
+        IfdFormat::Ascii, -1i32, -1i32, strpass),
+
         0xa433 =>
         (ExifTag::LensMake, "none",
         IfdFormat::Ascii, -1i32, -1i32, strpass),
@@ -266,6 +301,10 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         (ExifTag::LensModel, "none",
         IfdFormat::Ascii, -1i32, -1i32, strpass),
 
+        0xa435 =>
+        (ExifTag::LensSerialNumber, "none",
+        IfdFormat::Ascii, -1i32, -1i32, strpass),
+
         0xa500 =>
         (ExifTag::Gamma, "none",
         IfdFormat::URational, 1, 1, rational_value),
@@ -283,6 +322,14 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         (ExifTag::ImageUniqueID, "none",
         IfdFormat::Ascii, -1i32, -1i32, strpass),
 
+        0x1001 =>
+        (ExifTag::RelatedImageWidth, "pixels",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0x1002 =>
+        (ExifTag::RelatedImageLength, "pixels",
+        IfdFormat::U16, 1, 1, strpass),
+
         0x0 =>
         (ExifTag::GPSVersionID, "none",
         IfdFormat::U8, 4, 4, strpass),
@@ -408,3 +455,37 @@ pub(crate) fn tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32
         IfdFormat::Unknown, -1i32, -1i32, unknown)
     }
 }
+
+/// The IFD where a tag belongs. Returns `None` for `UnknownToMe`.
+pub(crate) fn tag_ifd_kind(tag: ExifTag) -> Option<IfdKind> {
+    Some(match tag {
+        ExifTag::UnknownToMe => return None,
+        ExifTag::ImageDescription | ExifTag::Make | ExifTag::Model | ExifTag::Orientation |
+        ExifTag::XResolution | ExifTag::YResolution | ExifTag::ResolutionUnit | ExifTag::Software |
+        ExifTag::DateTime | ExifTag::Artist | ExifTag::HostComputer | ExifTag::WhitePoint | ExifTag::PrimaryChromaticities |
+        ExifTag::YCbCrCoefficients | ExifTag::ReferenceBlackWhite | ExifTag::Copyright | ExifTag::XMP |
+        ExifTag::InterColorProfile | ExifTag::SubIFDs | ExifTag::ExifOffset | ExifTag::GPSOffset => IfdKind::Ifd0,
+        ExifTag::JPEGInterchangeFormat | ExifTag::JPEGInterchangeFormatLength => IfdKind::Ifd1,
+        ExifTag::InteroperabilityIndex | ExifTag::InteroperabilityVersion |
+        ExifTag::RelatedImageWidth | ExifTag::RelatedImageLength => IfdKind::Interoperability,
+        // GPS tags are numbered from 0
+        tag if (tag as u32) <= 0x1f => IfdKind::Gps,
+        tag if (tag as u32) >> 16 != Namespace::Standard as u32 => IfdKind::Makernote,
+        _ => IfdKind::Exif,
+    })
+}
+
+/// Same as `tag_to_exif`, but for tags found in the Interoperability IFD, whose codes overlap with GPS tags
+pub(crate) fn interop_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0001 =>
+        (ExifTag::InteroperabilityIndex, "none",
+        IfdFormat::Ascii, -1i32, -1i32, interop_index),
+
+        0x0002 =>
+        (ExifTag::InteroperabilityVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        _ => tag_to_exif(f),
+    }
+}
@@ -326,6 +326,20 @@ pub(crate) fn gpsmeasuremode(_tag: u16, e: &TagValue) -> Option<Cow<'static, str
     }
 }
 
+pub(crate) fn interop_index(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::Ascii(ref v) => {
+            Some(match v.as_str() {
+                "R98" => "R98 - DCF basic file (sRGB)".into(),
+                "R03" => "R03 - DCF option file (Adobe RGB)".into(),
+                "THM" => "THM - DCF thumbnail file".into(),
+                _ => format!("Unknown ({v})").into(),
+            })
+        },
+        _ => None,
+    }
+}
+
 /// Interprets an Undefined tag as ASCII, when the contents are guaranteed
 /// by EXIF standard to be ASCII-compatible. This function accepts UTF-8
 /// strings, should they be accepted by EXIF standard in the future.
@@ -386,6 +400,14 @@ pub(crate) fn undefined_as_blob(_tag: u16, e: &TagValue) -> Option<Cow<'static,
     }
 }
 
+/// Same as `undefined_as_blob`, but also for tags that are stored as an array of bytes
+pub(crate) fn bytes_as_blob(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U8(ref v) | TagValue::Undefined(ref v, _) => Some(format!("Blob of {} bytes", v.len()).into()),
+        _ => None,
+    }
+}
+
 pub(crate) fn apex_tv(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
     match *e {
         TagValue::IRational(ref v) => Some(format!("{:.1} Tv APEX", v.first()?.value()).into()),
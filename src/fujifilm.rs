@@ -0,0 +1,112 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Fujifilm `MakerNote` IFD
+pub(crate) fn fujifilm_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0000 =>
+        (ExifTag::FujifilmVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x0010 =>
+        (ExifTag::FujifilmInternalSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x1000 =>
+        (ExifTag::FujifilmQuality, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x1001 =>
+        (ExifTag::FujifilmSharpness, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0x1002 =>
+        (ExifTag::FujifilmWhiteBalance, "none",
+        IfdFormat::U16, 1, 1, fujifilm_white_balance),
+
+        0x1021 =>
+        (ExifTag::FujifilmFocusMode, "none",
+        IfdFormat::U16, 1, 1, fujifilm_focus_mode),
+
+        0x1031 =>
+        (ExifTag::FujifilmPictureMode, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0x1400 =>
+        (ExifTag::FujifilmDynamicRange, "none",
+        IfdFormat::U16, 1, 1, fujifilm_dynamic_range),
+
+        0x1404 =>
+        (ExifTag::FujifilmMinFocalLength, "mm",
+        IfdFormat::URational, 1, 1, focal_length),
+
+        0x1405 =>
+        (ExifTag::FujifilmMaxFocalLength, "mm",
+        IfdFormat::URational, 1, 1, focal_length),
+
+        0x1438 =>
+        (ExifTag::FujifilmImageCount, "none",
+        IfdFormat::U16, 1, 1, fujifilm_image_count),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn fujifilm_white_balance(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                0x0 => "Auto",
+                0x100 => "Daylight",
+                0x200 => "Cloudy",
+                0x300 => "Daylight Fluorescent",
+                0x301 => "Day White Fluorescent",
+                0x302 => "White Fluorescent",
+                0x400 => "Incandescent",
+                0x500 => "Flash",
+                0x600 => "Underwater",
+                0xf00 => "Custom",
+                0xff0 => "Kelvin",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn fujifilm_focus_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                0 => "Auto",
+                1 => "Manual",
+                65535 => "Movie",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn fujifilm_dynamic_range(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "Standard",
+                3 => "Wide",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+/// The top bit is a flag, not a part of the count
+fn fujifilm_image_count(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => Some((v.first()? & 0x7fff).to_string().into()),
+        _ => None,
+    }
+}
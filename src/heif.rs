@@ -0,0 +1,159 @@
+use super::image::FileType;
+use super::isobmff::*;
+use super::lowlevel::{read_u16, read_u32};
+use super::types::*;
+use std::borrow::Cow;
+
+/// HEIC, AVIF, or other HEIF file, depending on the brands in `ftyp`
+pub(crate) fn heif_type(contents: &[u8]) -> Option<FileType> {
+    let mut heif = false;
+    for brand in brands(contents)? {
+        match brand {
+            b"avif" | b"avis" => return Some(FileType::AVIF),
+            b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" => return Some(FileType::HEIC),
+            b"mif1" | b"msf1" => heif = true,
+            _ => {},
+        }
+    }
+    if heif { Some(FileType::HEIF) } else { None }
+}
+
+/// An item of the `meta` box, and where its data is
+struct ItemLocation {
+    item_id: u32,
+    /// 0 = offsets in the file, 1 = offsets in the `idat` box
+    construction_method: u16,
+    extents: Vec<(u64, u64)>,
+}
+
+/// Information from the `meta` box of a HEIF or AVIF file
+struct HeifMeta<'a> {
+    /// Item IDs and types, and content types of `mime` items
+    items: Vec<(u32, &'a [u8], &'a [u8])>,
+    locations: Vec<ItemLocation>,
+    idat: &'a [u8],
+}
+
+impl<'a> HeifMeta<'a> {
+    fn parse(contents: &'a [u8]) -> Option<Self> {
+        // meta is a full box, with version and flags before the children
+        let meta = find_box(contents, b"meta")?.get(4..)?;
+        Some(Self {
+            items: find_box(meta, b"iinf").map(parse_iinf).unwrap_or_default(),
+            locations: parse_iloc(find_box(meta, b"iloc")?)?,
+            idat: find_box(meta, b"idat").unwrap_or_default(),
+        })
+    }
+
+    fn item_id(&self, item_type: &[u8], content_type: Option<&[u8]>) -> Option<u32> {
+        self.items.iter()
+            .find(|&&(_, kind, content)| kind == item_type && content_type.map_or(true, |c| c == content))
+            .map(|&(id, ..)| id)
+    }
+
+    /// Data of the item, concatenated from its extents
+    fn item_data(&self, contents: &'a [u8], item_id: u32) -> Option<Cow<'a, [u8]>> {
+        let location = self.locations.iter().find(|l| l.item_id == item_id)?;
+        let source = match location.construction_method {
+            0 => contents,
+            1 => self.idat,
+            _ => return None,
+        };
+        let extent = |&(offset, length): &(u64, u64)| {
+            let start = usize::try_from(offset).ok()?;
+            let end = start.checked_add(usize::try_from(length).ok()?)?;
+            source.get(start..end)
+        };
+        match &location.extents[..] {
+            [single] => extent(single).map(Cow::Borrowed),
+            extents => {
+                let parts = extents.iter().map(extent).collect::<Option<Vec<_>>>()?;
+                Some(Cow::Owned(parts.concat()))
+            },
+        }
+    }
+}
+
+/// Item types from `infe` boxes (version 2 and newer)
+fn parse_iinf(iinf: &[u8]) -> Vec<(u32, &[u8], &[u8])> {
+    let version = iinf.first().copied().unwrap_or(0);
+    let entries = iinf.get(if version == 0 { 6 } else { 8 }..).unwrap_or_default();
+    iso_boxes(entries).filter(|b| b.kind == b"infe").filter_map(|infe| {
+        let data = infe.data;
+        let version = *data.first()?;
+        let (item_id, rest) = match version {
+            2 => (u32::from(read_u16(false, data.get(4..)?)?), data.get(6..)?),
+            3 => (read_u32(false, data.get(4..)?)?, data.get(8..)?),
+            _ => return None,
+        };
+        // protection index, item type, item name, content type
+        let item_type = rest.get(2..6)?;
+        let mut strings = rest.get(6..)?.split(|&c| c == 0);
+        strings.next();
+        let content_type = if item_type == b"mime" { strings.next().unwrap_or_default() } else { &[][..] };
+        Some((item_id, item_type, content_type))
+    }).collect()
+}
+
+fn parse_iloc(iloc: &[u8]) -> Option<Vec<ItemLocation>> {
+    let version = *iloc.first()?;
+    let offset_size = iloc.get(4)? >> 4;
+    let length_size = iloc.get(4)? & 0x0f;
+    let base_offset_size = iloc.get(5)? >> 4;
+    let index_size = if version > 0 { iloc.get(5)? & 0x0f } else { 0 };
+    let mut pos = 6;
+    let item_count = if version < 2 {
+        pos += 2;
+        u32::from(read_u16(false, iloc.get(6..)?)?)
+    } else {
+        pos += 4;
+        read_u32(false, iloc.get(6..)?)?
+    };
+
+    let mut locations = Vec::new();
+    for _ in 0..item_count {
+        let item_id = if version < 2 {
+            pos += 2;
+            u32::from(read_u16(false, iloc.get(pos - 2..)?)?)
+        } else {
+            pos += 4;
+            read_u32(false, iloc.get(pos - 4..)?)?
+        };
+        let construction_method = if version > 0 {
+            pos += 2;
+            read_u16(false, iloc.get(pos - 2..)?)? & 0x0f
+        } else {
+            0
+        };
+        // data reference index
+        pos += 2;
+        let base_offset = read_sized(iloc, &mut pos, base_offset_size)?;
+        let extent_count = read_u16(false, iloc.get(pos..)?)?;
+        pos += 2;
+        let mut extents = Vec::with_capacity(extent_count.into());
+        for _ in 0..extent_count {
+            read_sized(iloc, &mut pos, index_size)?;
+            let offset = read_sized(iloc, &mut pos, offset_size)?;
+            let length = read_sized(iloc, &mut pos, length_size)?;
+            extents.push((base_offset.checked_add(offset)?, length));
+        }
+        locations.push(ItemLocation { item_id, construction_method, extents });
+    }
+    Some(locations)
+}
+
+/// Finds the TIFF in the `Exif` item
+pub(crate) fn find_exif_in_heif(contents: &[u8]) -> Result<Cow<'_, [u8]>, ExifError> {
+    let not_found = |msg: &str| ExifError::ImageWithoutExif(msg.into());
+    let meta = HeifMeta::parse(contents).ok_or_else(|| not_found("HEIF meta box is missing or malformed"))?;
+    let item_id = meta.item_id(b"Exif", None).ok_or_else(|| not_found("HEIF has no Exif item"))?;
+    let data = meta.item_data(contents, item_id).ok_or_else(|| not_found("HEIF Exif item is out of bounds"))?;
+    skip_tiff_header_offset(data).ok_or_else(|| not_found("HEIF Exif item truncated"))
+}
+
+/// XMP is stored in a `mime` item
+pub(crate) fn find_xmp_in_heif(contents: &[u8]) -> Option<Vec<u8>> {
+    let meta = HeifMeta::parse(contents)?;
+    let item_id = meta.item_id(b"mime", Some(b"application/rdf+xml"))?;
+    meta.item_data(contents, item_id).map(Cow::into_owned)
+}
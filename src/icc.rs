@@ -0,0 +1,121 @@
+use super::image::JpegSegments;
+use super::lowlevel::{read_u16, read_u32};
+use super::types::*;
+
+pub(crate) const ICC_SIGNATURE: &[u8] = b"ICC_PROFILE\0";
+
+/// The fixed-size header of an ICC color profile, and its description
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IccHeader {
+    /// Size of the whole profile in bytes
+    pub size: u32,
+    /// Major, minor and bug-fix version of the ICC specification
+    pub version: (u8, u8, u8),
+    /// Profile/device class, e.g. "mntr" (display), "prtr" (output), "spac" (color space)
+    pub class: String,
+    /// Color space of the data, e.g. "RGB", "CMYK", "GRAY"
+    pub color_space: String,
+    /// Profile connection space, "XYZ" or "Lab"
+    pub pcs: String,
+    /// Text of the `desc` tag, e.g. "sRGB IEC61966-2.1"
+    pub description: Option<String>,
+}
+
+impl IccHeader {
+    /// Parses the header of an ICC profile. Returns `None` if the data isn't an ICC profile.
+    #[must_use]
+    pub fn parse(profile: &[u8]) -> Option<Self> {
+        if profile.len() < 128 || &profile[36..40] != b"acsp" {
+            return None;
+        }
+        Some(Self {
+            size: read_u32(false, profile)?,
+            version: (profile[8], profile[9] >> 4, profile[9] & 0x0f),
+            class: four_cc(&profile[12..16]),
+            color_space: four_cc(&profile[16..20]),
+            pcs: four_cc(&profile[20..24]),
+            description: find_tag(profile, b"desc").and_then(description),
+        })
+    }
+}
+
+fn four_cc(sig: &[u8]) -> String {
+    String::from_utf8_lossy(sig).trim_end().to_string()
+}
+
+/// Contents of a tag from the tag table that follows the header
+fn find_tag<'a>(profile: &'a [u8], sig: &[u8]) -> Option<&'a [u8]> {
+    let count = read_u32(false, profile.get(128..)?)? as usize;
+    // the count isn't trusted, only the entries in the profile are read
+    profile.get(132..)?.chunks_exact(12).take(count).find_map(|entry| {
+        if &entry[..4] != sig {
+            return None;
+        }
+        let offset = read_u32(false, &entry[4..])? as usize;
+        let size = read_u32(false, &entry[8..])? as usize;
+        profile.get(offset..offset.checked_add(size)?)
+    })
+}
+
+/// ICC v2 uses `textDescriptionType` (ASCII), and v4 uses `multiLocalizedUnicodeType` (UTF-16)
+fn description(tag: &[u8]) -> Option<String> {
+    match tag.get(..4)? {
+        b"desc" => {
+            let len = read_u32(false, tag.get(8..)?)? as usize;
+            let text = tag.get(12..len.checked_add(12)?)?;
+            let text = text.split(|&c| c == 0).next().unwrap_or_default();
+            Some(String::from_utf8_lossy(text).into_owned())
+        },
+        b"mluc" => {
+            let count = read_u32(false, tag.get(8..)?)?;
+            if count == 0 {
+                return None;
+            }
+            // the first record is the default language
+            let len = read_u32(false, tag.get(20..)?)? as usize;
+            let offset = read_u32(false, tag.get(24..)?)? as usize;
+            let text = tag.get(offset..offset.checked_add(len)?)?;
+            let utf16 = text.chunks_exact(2).map(|c| read_u16(false, c).unwrap());
+            Some(char::decode_utf16(utf16).map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)).collect())
+        },
+        _ => None,
+    }
+}
+
+/// Reassembles the ICC profile from APP2 segments of a JPEG. Each segment has a 1-based
+/// sequence number and the total number of segments.
+pub(crate) fn find_icc_in_jpeg(contents: &[u8], warnings: &mut Vec<String>) -> Option<Vec<u8>> {
+    let mut chunks = Vec::new();
+    for segment in JpegSegments::new(contents).map_while(Result::ok) {
+        if segment.marker != 0xe2 {
+            continue;
+        }
+        if let Some(chunk) = segment.data.strip_prefix(ICC_SIGNATURE) {
+            if chunk.len() < 2 {
+                warnings.push("ICC profile chunk truncated".into());
+                continue;
+            }
+            chunks.push((chunk[0], chunk[1], &chunk[2..]));
+        }
+    }
+    if chunks.is_empty() {
+        return None;
+    }
+
+    chunks.sort_by_key(|&(seq, ..)| seq);
+    let total = chunks[0].1;
+    let complete = chunks.len() == total as usize
+        && chunks.iter().enumerate().all(|(i, &(seq, count, _))| seq as usize == i + 1 && count == total);
+    if !complete {
+        warnings.push("ICC profile chunks are missing or inconsistent".into());
+        return None;
+    }
+    Some(chunks.into_iter().flat_map(|(.., data)| data).copied().collect())
+}
+
+/// In TIFF files the profile is stored in a tag of IFD0
+pub(crate) fn find_icc_in_entries(entries: &[ExifEntry]) -> Option<Vec<u8>> {
+    entries.iter()
+        .find(|e| e.kind == IfdKind::Ifd0 && e.tag == ExifTag::InterColorProfile)
+        .map(|e| e.ifd.data.clone())
+}
@@ -72,11 +72,11 @@ pub(crate) fn tag_value_new(f: &IfdEntry) -> Option<TagValue> {
             TagValue::I32(a)
         },
         IfdFormat::F32 => {
-            let a = read_f32_array(f.count, &f.data)?;
+            let a = read_f32_array(f.le, f.count, &f.data)?;
             TagValue::F32(a)
         },
         IfdFormat::F64 => {
-            let a = read_f64_array(f.count, &f.data)?;
+            let a = read_f64_array(f.le, f.count, &f.data)?;
             TagValue::F64(a)
         },
         IfdFormat::URational => {
@@ -96,6 +96,40 @@ pub(crate) fn tag_value_new(f: &IfdEntry) -> Option<TagValue> {
     })
 }
 
+/// Convert a `TagValue` into the format, count and raw data of an `IfdEntry`, in the given byte order.
+/// Strings get a NUL terminator.
+pub(crate) fn tag_value_encode(value: &TagValue, le: bool) -> (IfdFormat, u32, Vec<u8>) {
+    macro_rules! encode {
+        ($values:expr) => {
+            $values.iter().flat_map(|v| if le { v.to_le_bytes() } else { v.to_be_bytes() }).collect::<Vec<u8>>()
+        };
+    }
+
+    let (format, data) = match value {
+        TagValue::U8(v) => (IfdFormat::U8, v.clone()),
+        TagValue::Ascii(s) => (IfdFormat::Ascii, [s.as_bytes(), &[0]].concat()),
+        TagValue::U16(v) => (IfdFormat::U16, encode!(v)),
+        TagValue::U32(v) => (IfdFormat::U32, encode!(v)),
+        TagValue::URational(v) => (IfdFormat::URational, encode!(v.iter().flat_map(|r| [r.numerator, r.denominator]).collect::<Vec<_>>())),
+        TagValue::I8(v) => (IfdFormat::I8, v.iter().map(|&i| i as u8).collect()),
+        TagValue::Undefined(v, _) => (IfdFormat::Undefined, v.clone()),
+        TagValue::I16(v) => (IfdFormat::I16, encode!(v)),
+        TagValue::I32(v) => (IfdFormat::I32, encode!(v)),
+        TagValue::IRational(v) => (IfdFormat::IRational, encode!(v.iter().flat_map(|r| [r.numerator, r.denominator]).collect::<Vec<_>>())),
+        TagValue::F32(v) => (IfdFormat::F32, encode!(v)),
+        TagValue::F64(v) => (IfdFormat::F64, encode!(v)),
+        TagValue::Unknown(v, _) => (IfdFormat::Unknown, v.clone()),
+        TagValue::Invalid(v, _, format, count) => return (IfdFormat::new(*format), *count, v.clone()),
+    };
+    let size = match format {
+        IfdFormat::U16 | IfdFormat::I16 => 2,
+        IfdFormat::U32 | IfdFormat::I32 | IfdFormat::F32 => 4,
+        IfdFormat::URational | IfdFormat::IRational | IfdFormat::F64 => 8,
+        _ => 1,
+    };
+    (format, (data.len() / size) as u32, data)
+}
+
 /// Compare two vectors of floats, and always consider NaN == NaN.
 fn vec_cmp_f32(va: &[f32], vb: &[f32]) -> bool {
     (va.len() == vb.len()) && // zip stops at the shortest
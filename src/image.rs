@@ -1,4 +1,11 @@
+use crate::heif::heif_type;
+use crate::jxl::is_jxl;
+use crate::mp4::video_type;
+use crate::png::PNG_SIGNATURE;
+use crate::raw::{is_cr3, is_raf, raw_type};
+use crate::tiff::tiff_byte_order;
 use crate::types::ExifError;
+use crate::webp::is_webp;
 
 use std::fmt::{self, Display};
 
@@ -7,6 +14,23 @@ pub enum FileType {
     Unknown,
     JPEG,
     TIFF,
+    PNG,
+    WEBP,
+    HEIC,
+    HEIF,
+    AVIF,
+    JXL,
+    DNG,
+    CR2,
+    NEF,
+    ARW,
+    ORF,
+    RW2,
+    PEF,
+    CR3,
+    RAF,
+    MP4,
+    MOV,
 }
 
 impl Display for FileType {
@@ -21,6 +45,23 @@ impl FileType {
             Self::Unknown => "application/octet-stream",
             Self::JPEG => "image/jpeg",
             Self::TIFF => "image/tiff",
+            Self::PNG => "image/png",
+            Self::WEBP => "image/webp",
+            Self::HEIC => "image/heic",
+            Self::HEIF => "image/heif",
+            Self::AVIF => "image/avif",
+            Self::JXL => "image/jxl",
+            Self::DNG => "image/x-adobe-dng",
+            Self::CR2 => "image/x-canon-cr2",
+            Self::NEF => "image/x-nikon-nef",
+            Self::ARW => "image/x-sony-arw",
+            Self::ORF => "image/x-olympus-orf",
+            Self::RW2 => "image/x-panasonic-rw2",
+            Self::PEF => "image/x-pentax-pef",
+            Self::CR3 => "image/x-canon-cr3",
+            Self::RAF => "image/x-fuji-raf",
+            Self::MP4 => "video/mp4",
+            Self::MOV => "video/quicktime",
         }
     }
 }
@@ -31,71 +72,153 @@ pub(crate) fn detect_type(contents: &[u8]) -> FileType {
         return FileType::Unknown;
     }
 
-    if contents[0] == 0xff && contents[1] == 0xd8 && contents[2] == 0xff && // contents[3] == 0xe0 &&
-        contents[6] == b'J' && contents[7] == b'F' && contents[8] == b'I' && contents[9] == b'F' &&
-            contents[10] == 0
-    {
+    if is_jpeg(contents) {
         return FileType::JPEG;
     }
-    if contents[0] == 0xff && contents[1] == 0xd8 && contents[2] == 0xff && // contents[3] == 0xe0
-        contents[6] == b'E' && contents[7] == b'x' && contents[8] == b'i' && contents[9] == b'f' &&
-            contents[10] == 0
-    {
-        return FileType::JPEG;
+    if tiff_byte_order(contents).is_some() {
+        return raw_type(contents).unwrap_or(FileType::TIFF);
+    }
+    if contents.starts_with(PNG_SIGNATURE) {
+        return FileType::PNG;
+    }
+    if is_webp(contents) {
+        return FileType::WEBP;
+    }
+    if is_cr3(contents) {
+        return FileType::CR3;
+    }
+    if let Some(heif) = heif_type(contents) {
+        return heif;
     }
-    if contents[0] == b'I' && contents[1] == b'I' && contents[2] == 42 && contents[3] == 0 {
-        /* TIFF little-endian */
-        return FileType::TIFF;
+    if let Some(video) = video_type(contents) {
+        return video;
     }
-    if contents[0] == b'M' && contents[1] == b'M' && contents[2] == 0 && contents[3] == 42 {
-        /* TIFF big-endian */
-        return FileType::TIFF;
+    if is_jxl(contents) {
+        return FileType::JXL;
+    }
+    if is_raf(contents) {
+        return FileType::RAF;
     }
     FileType::Unknown
 }
 
-/// Find the embedded TIFF in a JPEG image (that in turn contains the EXIF data)
-pub fn find_embedded_tiff_in_jpeg(contents: &[u8]) -> Result<(usize, usize), ExifError> {
-    let mut offset = 2_usize;
+/// A JPEG starts with the SOI marker, followed by a well-formed segment.
+/// The first segment can be any of APPn, COM, DQT, etc.
+fn is_jpeg(contents: &[u8]) -> bool {
+    contents.starts_with(&[0xff, 0xd8]) && matches!(JpegSegments::new(contents).next(), Some(Ok(_)))
+}
 
-    while offset < contents.len() {
-        if contents.len() < (offset + 4) {
-            return Err(ExifError::JpegWithoutExif("JPEG truncated in marker header".into()));
-        }
+/// A marker segment of a JPEG file
+pub(crate) struct JpegSegment<'a> {
+    /// The second byte of the marker, e.g. 0xE1 for APP1
+    pub marker: u8,
+    /// Position of the segment's marker in the file
+    pub start: usize,
+    /// Contents of the segment, excluding the marker and the size
+    pub data: &'a [u8],
+}
 
-        let marker: u16 = u16::from(contents[offset]) * 256 + u16::from(contents[offset + 1]);
+impl JpegSegment<'_> {
+    /// Position of the segment's contents in the file
+    pub fn data_start(&self) -> usize {
+        self.start + 4
+    }
+}
 
-        if marker < 0xff00 {
-            return Err(ExifError::JpegWithoutExif(format!("Invalid marker {marker:x}")));
-        }
+/// Iterates over the segments of a JPEG file, from SOI up to and including SOS
+pub(crate) struct JpegSegments<'a> {
+    contents: &'a [u8],
+    offset: usize,
+    done: bool,
+}
 
-        offset += 2;
-        let size = (contents[offset] as usize) * 256 + (contents[offset + 1] as usize);
+impl<'a> JpegSegments<'a> {
+    pub fn new(contents: &'a [u8]) -> Self {
+        Self { contents, offset: 2, done: false }
+    }
 
-        if size < 2 {
-            return Err(ExifError::JpegWithoutExif("JPEG marker size must be at least 2 (because of the size word)".into()));
-        }
-        if contents.len() < (offset + size) {
-            return Err(ExifError::JpegWithoutExif("JPEG truncated in marker body".into()));
-        }
+    fn next_segment(&mut self) -> Result<Option<JpegSegment<'a>>, ExifError> {
+        let contents = self.contents;
 
-        if marker == 0xffe1 {
-            if size < 8 {
-                return Err(ExifError::JpegWithoutExif("EXIF preamble truncated".into()));
+        loop {
+            let mut offset = self.offset;
+            if offset >= contents.len() {
+                return Ok(None);
+            }
+            if contents[offset] != 0xff {
+                return Err(ExifError::JpegWithoutExif(format!("Invalid marker {:x}", contents[offset])));
             }
 
-            if contents[offset + 2..offset + 8] != [b'E', b'x', b'i', b'f', 0, 0] {
-                return Err(ExifError::JpegWithoutExif("EXIF preamble unrecognized".into()));
+            // Any marker may be preceded by fill bytes
+            while contents.get(offset + 1) == Some(&0xff) {
+                offset += 1;
+            }
+            if contents.len() < (offset + 2) {
+                return Err(ExifError::JpegWithoutExif("JPEG truncated in marker header".into()));
             }
 
+            let marker = contents[offset + 1];
+            match marker {
+                0x00 => return Err(ExifError::JpegWithoutExif("Invalid marker ff00".into())),
+                // TEM, RSTn and SOI have no size and no contents
+                0x01 | 0xd0..=0xd8 => {
+                    self.offset = offset + 2;
+                    continue;
+                },
+                // end of image
+                0xd9 => return Ok(None),
+                _ => {},
+            }
+
+            if contents.len() < (offset + 4) {
+                return Err(ExifError::JpegWithoutExif("JPEG truncated in marker header".into()));
+            }
+            let size = (contents[offset + 2] as usize) * 256 + (contents[offset + 3] as usize);
+            if size < 2 {
+                return Err(ExifError::JpegWithoutExif("JPEG marker size must be at least 2 (because of the size word)".into()));
+            }
+            let data = contents.get(offset + 4..offset + 2 + size)
+                .ok_or_else(|| ExifError::JpegWithoutExif("JPEG truncated in marker body".into()))?;
+
+            self.offset = offset + 2 + size;
+            if marker == 0xda {
+                // start of scan is the last marker before the entropy-coded data
+                self.done = true;
+            }
+            return Ok(Some(JpegSegment { marker, start: offset, data }));
+        }
+    }
+}
+
+impl<'a> Iterator for JpegSegments<'a> {
+    type Item = Result<JpegSegment<'a>, ExifError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_segment().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// Find the embedded TIFF in a JPEG image (that in turn contains the EXIF data)
+pub fn find_embedded_tiff_in_jpeg(contents: &[u8]) -> Result<(usize, usize), ExifError> {
+    for segment in JpegSegments::new(contents) {
+        let segment = segment?;
+
+        // APP1 is also used for XMP and other payloads, which are skipped
+        if segment.marker == 0xe1 && segment.data.starts_with(b"Exif\0\0") {
             // The offset and size of the block, excluding size and 'Exif\0\0'.
-            return Ok((offset + 8, size - 8));
+            return Ok((segment.data_start() + 6, segment.data.len() - 6));
         }
-        if marker == 0xffda {
+        if segment.marker == 0xda {
             // last marker
             return Err(ExifError::JpegWithoutExif("Last mark found and no EXIF".into()));
         }
-        offset += size;
     }
 
     Err(ExifError::JpegWithoutExif("Scan past EOF and no EXIF found".into()))
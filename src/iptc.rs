@@ -0,0 +1,320 @@
+use super::image::JpegSegments;
+use super::lowlevel::{read_u16, read_u32};
+use std::fmt::{self, Display};
+
+const PHOTOSHOP_SIGNATURE: &[u8] = b"Photoshop 3.0\0";
+const IPTC_RESOURCE_ID: u16 = 0x0404;
+
+/// IPTC-IIM dataset, identified by the record number (most significant byte)
+/// and the dataset number (least significant byte)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(u16)]
+pub enum IptcTag {
+    /// Datasets not recognized are still available with their record and dataset numbers
+    UnknownToMe = 0xffff,
+    EnvelopeRecordVersion = 0x0100,
+    CodedCharacterSet = 0x015a,
+    RecordVersion = 0x0200,
+    ObjectName = 0x0205,
+    EditStatus = 0x0207,
+    Urgency = 0x020a,
+    Category = 0x020f,
+    SupplementalCategories = 0x0214,
+    Keywords = 0x0219,
+    SpecialInstructions = 0x0228,
+    DateCreated = 0x0237,
+    TimeCreated = 0x023c,
+    DigitalCreationDate = 0x023e,
+    DigitalCreationTime = 0x023f,
+    OriginatingProgram = 0x0241,
+    ProgramVersion = 0x0246,
+    ByLine = 0x0250,
+    ByLineTitle = 0x0255,
+    City = 0x025a,
+    SubLocation = 0x025c,
+    ProvinceState = 0x025f,
+    CountryCode = 0x0264,
+    CountryName = 0x0265,
+    OriginalTransmissionReference = 0x0267,
+    Headline = 0x0269,
+    Credit = 0x026e,
+    Source = 0x0273,
+    CopyrightNotice = 0x0274,
+    Contact = 0x0276,
+    Caption = 0x0278,
+    Writer = 0x027a,
+}
+
+impl Display for IptcTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match *self {
+            Self::UnknownToMe => "Unknown to this library, or manufacturer-specific",
+            Self::EnvelopeRecordVersion => "Envelope record version",
+            Self::CodedCharacterSet => "Coded character set",
+            Self::RecordVersion => "Record version",
+            Self::ObjectName => "Object name",
+            Self::EditStatus => "Edit status",
+            Self::Urgency => "Urgency",
+            Self::Category => "Category",
+            Self::SupplementalCategories => "Supplemental categories",
+            Self::Keywords => "Keywords",
+            Self::SpecialInstructions => "Special instructions",
+            Self::DateCreated => "Date created",
+            Self::TimeCreated => "Time created",
+            Self::DigitalCreationDate => "Digital creation date",
+            Self::DigitalCreationTime => "Digital creation time",
+            Self::OriginatingProgram => "Originating program",
+            Self::ProgramVersion => "Program version",
+            Self::ByLine => "By-line",
+            Self::ByLineTitle => "By-line title",
+            Self::City => "City",
+            Self::SubLocation => "Sub-location",
+            Self::ProvinceState => "Province/State",
+            Self::CountryCode => "Country code",
+            Self::CountryName => "Country name",
+            Self::OriginalTransmissionReference => "Original transmission reference",
+            Self::Headline => "Headline",
+            Self::Credit => "Credit",
+            Self::Source => "Source",
+            Self::CopyrightNotice => "Copyright notice",
+            Self::Contact => "Contact",
+            Self::Caption => "Caption/Abstract",
+            Self::Writer => "Caption writer",
+        })
+    }
+}
+
+/// Value of an IPTC dataset
+#[derive(Clone, Debug, PartialEq)]
+pub enum IptcValue {
+    Text(String),
+    /// Binary number, used by the record versions
+    Number(u32),
+    /// Date from "CCYYMMDD"
+    Date { year: u16, month: u8, day: u8 },
+    /// Time from "HHMMSS±HHMM", with the offset from UTC in minutes
+    Time { hour: u8, minute: u8, second: u8, utc_offset: i16 },
+    /// Value of an unknown, or malformed dataset
+    Binary(Vec<u8>),
+}
+
+impl Display for IptcValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(s) => f.write_str(s),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Date { year, month, day } => write!(f, "{year:04}-{month:02}-{day:02}"),
+            Self::Time { hour, minute, second, utc_offset } => {
+                let sign = if *utc_offset < 0 { '-' } else { '+' };
+                let offset = utc_offset.unsigned_abs();
+                write!(f, "{hour:02}:{minute:02}:{second:02}{sign}{:02}:{:02}", offset / 60, offset % 60)
+            },
+            Self::Binary(data) => write!(f, "Blob of {} bytes", data.len()),
+        }
+    }
+}
+
+/// A dataset of the IPTC-IIM metadata. Repeatable datasets, like `Keywords`,
+/// have an entry for every value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IptcEntry {
+    pub tag: IptcTag,
+    pub record: u8,
+    pub dataset: u8,
+    pub value: IptcValue,
+}
+
+/// A block from the Photoshop image resources (in JPEG's APP13 segments)
+pub(crate) struct PhotoshopResource<'a> {
+    pub id: u16,
+    pub data: &'a [u8],
+}
+
+/// Parses image resource blocks. Malformed data ends the list.
+pub(crate) fn photoshop_resources(mut data: &[u8]) -> Vec<PhotoshopResource<'_>> {
+    let mut resources = Vec::new();
+    while data.len() >= 12 {
+        if !matches!(&data[..4], b"8BIM" | b"MeSa" | b"PHUT" | b"AgHg" | b"DCSR") {
+            break;
+        }
+        let id = read_u16(false, &data[4..]).unwrap();
+        // name is a Pascal string, padded to even size
+        let name_len = (1 + data[6] as usize + 1) & !1;
+        let size_pos = 6 + name_len;
+        let size = match data.get(size_pos..).and_then(|d| read_u32(false, d)) {
+            Some(size) => size as usize,
+            None => break,
+        };
+        let start = size_pos + 4;
+        let resource = match data.get(start..start + size) {
+            Some(resource) => resource,
+            None => break,
+        };
+        resources.push(PhotoshopResource { id, data: resource });
+        data = data.get(start + ((size + 1) & !1)..).unwrap_or_default();
+    }
+    resources
+}
+
+/// Finds the IPTC resource in APP13 segments of a JPEG. Resources that don't fit in one segment
+/// continue in the next one.
+pub(crate) fn find_iptc_in_jpeg(contents: &[u8], warnings: &mut Vec<String>) -> Vec<IptcEntry> {
+    let mut resources = Vec::new();
+    for segment in JpegSegments::new(contents).map_while(Result::ok) {
+        if segment.marker == 0xed {
+            if let Some(data) = segment.data.strip_prefix(PHOTOSHOP_SIGNATURE) {
+                resources.extend_from_slice(data);
+            }
+        }
+    }
+
+    photoshop_resources(&resources).iter()
+        .filter(|r| r.id == IPTC_RESOURCE_ID)
+        .flat_map(|r| parse_iptc(r.data, warnings))
+        .collect()
+}
+
+/// Parses IPTC-IIM datasets
+pub(crate) fn parse_iptc(mut data: &[u8], warnings: &mut Vec<String>) -> Vec<IptcEntry> {
+    let mut entries = Vec::new();
+    let mut utf8 = false;
+    while data.len() >= 5 && data[0] == 0x1c {
+        let record = data[1];
+        let dataset = data[2];
+        let mut len = read_u16(false, &data[3..]).unwrap() as usize;
+        let mut start = 5;
+        if len & 0x8000 != 0 {
+            // extended dataset, the size is in the next (len & 0x7fff) bytes
+            let size_len = len & 0x7fff;
+            let size = match data.get(5..5 + size_len) {
+                Some(size) if size_len <= 4 => size,
+                _ => {
+                    warnings.push(format!("IPTC dataset {record}:{dataset} has invalid extended size"));
+                    return entries;
+                },
+            };
+            len = size.iter().fold(0, |len, &b| (len << 8) | b as usize);
+            start += size_len;
+        }
+        let value = match data.get(start..start + len) {
+            Some(value) => value,
+            None => {
+                warnings.push(format!("IPTC dataset {record}:{dataset} truncated"));
+                return entries;
+            },
+        };
+        data = &data[start + len..];
+
+        let tag = iptc_tag(record, dataset);
+        if tag == IptcTag::CodedCharacterSet {
+            // ESC % G selects UTF-8
+            utf8 = value == b"\x1b%G";
+        }
+        entries.push(IptcEntry {
+            tag,
+            record,
+            dataset,
+            value: iptc_value(tag, value, utf8),
+        });
+    }
+    entries
+}
+
+fn iptc_tag(record: u8, dataset: u8) -> IptcTag {
+    match u16::from(record) << 8 | u16::from(dataset) {
+        0x0100 => IptcTag::EnvelopeRecordVersion,
+        0x015a => IptcTag::CodedCharacterSet,
+        0x0200 => IptcTag::RecordVersion,
+        0x0205 => IptcTag::ObjectName,
+        0x0207 => IptcTag::EditStatus,
+        0x020a => IptcTag::Urgency,
+        0x020f => IptcTag::Category,
+        0x0214 => IptcTag::SupplementalCategories,
+        0x0219 => IptcTag::Keywords,
+        0x0228 => IptcTag::SpecialInstructions,
+        0x0237 => IptcTag::DateCreated,
+        0x023c => IptcTag::TimeCreated,
+        0x023e => IptcTag::DigitalCreationDate,
+        0x023f => IptcTag::DigitalCreationTime,
+        0x0241 => IptcTag::OriginatingProgram,
+        0x0246 => IptcTag::ProgramVersion,
+        0x0250 => IptcTag::ByLine,
+        0x0255 => IptcTag::ByLineTitle,
+        0x025a => IptcTag::City,
+        0x025c => IptcTag::SubLocation,
+        0x025f => IptcTag::ProvinceState,
+        0x0264 => IptcTag::CountryCode,
+        0x0265 => IptcTag::CountryName,
+        0x0267 => IptcTag::OriginalTransmissionReference,
+        0x0269 => IptcTag::Headline,
+        0x026e => IptcTag::Credit,
+        0x0273 => IptcTag::Source,
+        0x0274 => IptcTag::CopyrightNotice,
+        0x0276 => IptcTag::Contact,
+        0x0278 => IptcTag::Caption,
+        0x027a => IptcTag::Writer,
+        _ => IptcTag::UnknownToMe,
+    }
+}
+
+fn iptc_value(tag: IptcTag, value: &[u8], utf8: bool) -> IptcValue {
+    let typed = match tag {
+        IptcTag::UnknownToMe | IptcTag::CodedCharacterSet => None,
+        IptcTag::EnvelopeRecordVersion | IptcTag::RecordVersion => {
+            read_u16(false, value).filter(|_| value.len() == 2).map(|n| IptcValue::Number(n.into()))
+        },
+        IptcTag::DateCreated | IptcTag::DigitalCreationDate => iptc_date(value),
+        IptcTag::TimeCreated | IptcTag::DigitalCreationTime => iptc_time(value),
+        _ => Some(IptcValue::Text(iptc_text(value, utf8))),
+    };
+    typed.unwrap_or_else(|| IptcValue::Binary(value.to_vec()))
+}
+
+/// Without `CodedCharacterSet` the encoding is unspecified. Files that are valid UTF-8 are
+/// assumed to be UTF-8, and everything else is assumed to be Latin-1.
+fn iptc_text(value: &[u8], utf8: bool) -> String {
+    match std::str::from_utf8(value) {
+        Ok(s) => s.into(),
+        Err(_) if utf8 => String::from_utf8_lossy(value).into_owned(),
+        Err(_) => value.iter().map(|&c| char::from(c)).collect(),
+    }
+}
+
+fn digits(value: &[u8]) -> Option<u32> {
+    if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(value.iter().fold(0, |n, &d| n * 10 + u32::from(d - b'0')))
+}
+
+fn iptc_date(value: &[u8]) -> Option<IptcValue> {
+    if value.len() != 8 {
+        return None;
+    }
+    Some(IptcValue::Date {
+        year: digits(&value[..4])? as u16,
+        month: digits(&value[4..6])? as u8,
+        day: digits(&value[6..])? as u8,
+    })
+}
+
+fn iptc_time(value: &[u8]) -> Option<IptcValue> {
+    let utc_offset = match value.len() {
+        6 => 0,
+        11 => {
+            let offset = (digits(&value[7..9])? * 60 + digits(&value[9..])?) as i16;
+            match value[6] {
+                b'+' => offset,
+                b'-' => -offset,
+                _ => return None,
+            }
+        },
+        _ => return None,
+    };
+    Some(IptcValue::Time {
+        hour: digits(&value[..2])? as u8,
+        minute: digits(&value[2..4])? as u8,
+        second: digits(&value[4..6])? as u8,
+        utc_offset,
+    })
+}
@@ -0,0 +1,64 @@
+use super::lowlevel::{read_u32, read_u64};
+use std::borrow::Cow;
+
+/// A box of the ISO base media file format (used by HEIF, AVIF, MP4, and others)
+pub(crate) struct IsoBox<'a> {
+    pub kind: &'a [u8],
+    /// Contents of the box, after the size and type
+    pub data: &'a [u8],
+}
+
+/// Iterates over boxes in a buffer, up to the first malformed box
+pub(crate) fn iso_boxes(contents: &[u8]) -> impl Iterator<Item = IsoBox<'_>> {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let rest = contents.get(pos..)?;
+        let kind = rest.get(4..8)?;
+        let (header_len, size) = match read_u32(false, rest)? {
+            // box extends to the end of the file
+            0 => (8, rest.len()),
+            1 => (16, usize::try_from(read_u64(false, rest.get(8..)?)?).ok()?),
+            size => (8, size as usize),
+        };
+        let data = rest.get(header_len..size)?;
+        pos += size;
+        Some(IsoBox { kind, data })
+    })
+}
+
+/// Contents of the first box of the given type
+pub(crate) fn find_box<'a>(contents: &'a [u8], kind: &[u8]) -> Option<&'a [u8]> {
+    iso_boxes(contents).find(|b| b.kind == kind).map(|b| b.data)
+}
+
+/// Major and compatible brands from the `ftyp` box at the start of the file
+pub(crate) fn brands(contents: &[u8]) -> Option<impl Iterator<Item = &[u8]>> {
+    let first = iso_boxes(contents).next()?;
+    if first.kind != b"ftyp" || first.data.len() < 8 {
+        return None;
+    }
+    // major brand, minor version, compatible brands
+    let major = first.data[..4].chunks_exact(4);
+    Some(major.chain(first.data[8..].chunks_exact(4)))
+}
+
+/// Reads an unsigned big-endian integer of 0, 4 or 8 bytes
+pub(crate) fn read_sized(data: &[u8], pos: &mut usize, size: u8) -> Option<u64> {
+    let value = match size {
+        0 => 0,
+        4 => u64::from(read_u32(false, data.get(*pos..)?)?),
+        8 => read_u64(false, data.get(*pos..)?)?,
+        _ => return None,
+    };
+    *pos += size as usize;
+    Some(value)
+}
+
+/// `Exif` items and boxes start with the offset of the TIFF header, which skips the "Exif\0\0" prefix
+pub(crate) fn skip_tiff_header_offset(data: Cow<'_, [u8]>) -> Option<Cow<'_, [u8]>> {
+    let start = (read_u32(false, &data)? as usize).checked_add(4).filter(|&start| start <= data.len())?;
+    Some(match data {
+        Cow::Borrowed(data) => Cow::Borrowed(&data[start..]),
+        Cow::Owned(data) => Cow::Owned(data[start..].to_vec()),
+    })
+}
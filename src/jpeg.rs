@@ -0,0 +1,64 @@
+use super::image::JpegSegments;
+use super::types::*;
+use std::io::Write;
+
+pub(crate) const APP0: u8 = 0xe0;
+pub(crate) const APP1: u8 = 0xe1;
+/// The 16-bit length of a segment includes the two bytes of the length itself
+const MAX_SEGMENT_DATA: usize = 0xffff - 2;
+
+/// Returns a copy of the JPEG file with the APP1 Exif segment replaced by the serialized `exif`.
+/// If the file has no EXIF, the segment is inserted after SOI and the JFIF APP0 segments.
+///
+/// Offsets inside the `MakerNote` are updated to its new position, see `MakernoteOffsets::Fix`.
+///
+/// Fails if the serialized EXIF doesn't fit in the 64KB limit of a JPEG segment.
+pub fn write_exif_to_jpeg(original: &[u8], exif: &ExifData) -> Result<Vec<u8>, ExifError> {
+    let mut jpeg = Vec::with_capacity(original.len() + MAX_SEGMENT_DATA);
+    write_exif_to_jpeg_into(original, exif, &mut jpeg)?;
+    Ok(jpeg)
+}
+
+/// Same as [`write_exif_to_jpeg`], but writes the JPEG file to `output`.
+/// Nothing is written if the EXIF can't be serialized.
+pub fn write_exif_to_jpeg_into<W: Write>(original: &[u8], exif: &ExifData, mut output: W) -> Result<(), ExifError> {
+    if !original.starts_with(&[0xff, 0xd8]) {
+        return Err(ExifError::FileTypeUnknown);
+    }
+    let segment = exif_segment(exif, MakernoteOffsets::Fix)?;
+    let (start, end) = exif_segment_range(original)?;
+
+    output.write_all(&original[..start])?;
+    output.write_all(&segment)?;
+    output.write_all(&original[end..])?;
+    Ok(())
+}
+
+/// The APP1 segment with the serialized `exif`, including the marker and the size
+pub(crate) fn exif_segment(exif: &ExifData, makernote_offsets: MakernoteOffsets) -> Result<Vec<u8>, ExifError> {
+    let serialized = exif.serialize_with(makernote_offsets)?;
+    // only JPEG EXIF is serialized with the header
+    let tiff = serialized.strip_prefix(EXIF_HEADER).unwrap_or(&serialized[..]);
+    let len = EXIF_HEADER.len() + tiff.len();
+    if len > MAX_SEGMENT_DATA {
+        return Err(ExifError::CannotWrite(format!("EXIF is {len} bytes, which is too large for a JPEG segment")));
+    }
+    Ok([&[0xff, APP1][..], &(len as u16 + 2).to_be_bytes(), EXIF_HEADER, tiff].concat())
+}
+
+/// Range of the existing Exif segment, or an empty range where the segment should be inserted
+fn exif_segment_range(jpeg: &[u8]) -> Result<(usize, usize), ExifError> {
+    let mut insert_at = 2;
+    for segment in JpegSegments::new(jpeg) {
+        let segment = segment?;
+        let end = segment.data_start() + segment.data.len();
+        if segment.marker == APP1 && segment.data.starts_with(EXIF_HEADER) {
+            return Ok((segment.start, end));
+        }
+        // JFIF requires APP0 to be right after SOI
+        if segment.marker == APP0 && segment.start == insert_at {
+            insert_at = end;
+        }
+    }
+    Ok((insert_at, insert_at))
+}
@@ -0,0 +1,71 @@
+use super::isobmff::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// The `JXL ` box that starts the container format
+pub(crate) const JXL_CONTAINER_SIGNATURE: &[u8] = b"\0\0\0\x0cJXL \r\n\x87\n";
+/// A bare codestream, which can't have any metadata
+const JXL_CODESTREAM_SIGNATURE: &[u8] = b"\xff\x0a";
+
+/// Compressed boxes are limited, because a small Brotli stream can decompress to gigabytes
+#[cfg(feature = "brotli")]
+const MAX_DECOMPRESSED_SIZE: usize = 1 << 24;
+
+pub(crate) fn is_jxl(contents: &[u8]) -> bool {
+    contents.starts_with(JXL_CONTAINER_SIGNATURE) || contents.starts_with(JXL_CODESTREAM_SIGNATURE)
+}
+
+/// Contents of the first box of the given type, which may be wrapped in a Brotli-compressed `brob` box
+fn find_jxl_box<'a>(contents: &'a [u8], kind: &[u8], warnings: &mut Vec<String>) -> Option<Cow<'a, [u8]>> {
+    for b in iso_boxes(contents) {
+        if b.kind == kind {
+            return Some(Cow::Borrowed(b.data));
+        }
+        // compressed box starts with the type of the box it wraps
+        if b.kind == b"brob" && b.data.get(..4) == Some(kind) {
+            match brotli_decompress(&b.data[4..]) {
+                Ok(data) => return Some(Cow::Owned(data)),
+                Err(msg) => warnings.push(format!("JPEG XL {} box: {msg}", String::from_utf8_lossy(kind))),
+            }
+        }
+    }
+    None
+}
+
+#[cfg(feature = "brotli")]
+fn brotli_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+
+    let mut decompressed = Vec::new();
+    brotli_decompressor::Decompressor::new(data, 4096)
+        .take(MAX_DECOMPRESSED_SIZE as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| format!("can't be decompressed: {e}"))?;
+    if decompressed.len() > MAX_DECOMPRESSED_SIZE {
+        return Err("decompressed size is too large".into());
+    }
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "brotli"))]
+fn brotli_decompress(_: &[u8]) -> Result<Vec<u8>, String> {
+    Err("compressed boxes need the brotli feature of rexif".into())
+}
+
+/// Finds the TIFF in the `Exif` box
+pub(crate) fn find_exif_in_jxl<'a>(contents: &'a [u8], warnings: &mut Vec<String>) -> Result<Cow<'a, [u8]>, ExifError> {
+    let not_found = |msg: &str| ExifError::ImageWithoutExif(msg.into());
+    if !contents.starts_with(JXL_CONTAINER_SIGNATURE) {
+        return Err(not_found("JPEG XL codestream without a container has no metadata"));
+    }
+    let data = find_jxl_box(contents, b"Exif", warnings).ok_or_else(|| not_found("JPEG XL has no Exif box"))?;
+    skip_tiff_header_offset(data).ok_or_else(|| not_found("JPEG XL Exif box truncated"))
+}
+
+/// XMP is stored in the `xml ` box
+pub(crate) fn find_xmp_in_jxl(contents: &[u8], warnings: &mut Vec<String>) -> Option<Vec<u8>> {
+    if !contents.starts_with(JXL_CONTAINER_SIGNATURE) {
+        return None;
+    }
+    find_jxl_box(contents, b"xml ", warnings).map(Cow::into_owned)
+}
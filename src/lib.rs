@@ -50,11 +50,45 @@ use self::image::*;
 mod ifdformat;
 mod tiff;
 use self::tiff::parse_tiff;
+pub use self::tiff::write_exif_to_tiff;
 mod exif;
 mod exifpost;
 mod exifreadable;
+mod makernote;
+mod nikon;
+mod canon;
+mod sony;
+mod fujifilm;
+mod olympus;
+mod panasonic;
+mod pentax;
+mod xmp;
+pub use self::xmp::*;
+mod iptc;
+pub use self::iptc::*;
+mod icc;
+pub use self::icc::*;
+mod png;
+use self::png::*;
+mod webp;
+pub use self::webp::*;
+mod jpeg;
+pub use self::jpeg::*;
+mod isobmff;
+mod heif;
+use self::heif::*;
+mod jxl;
+use self::jxl::*;
+mod raw;
+use self::raw::*;
+mod mp4;
+use self::mp4::*;
+mod mpf;
+pub use self::mpf::*;
+mod scrub;
+pub use self::scrub::*;
 
-/// Parse a byte buffer that should contain a TIFF or JPEG image.
+/// Parse a byte buffer that should contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF, JPEG XL or camera RAW image, or an MP4 or QuickTime video.
 /// Tries to detect format and parse EXIF data.
 ///
 /// Prints warnings to stderr.
@@ -64,27 +98,80 @@ pub fn parse_buffer(contents: &[u8]) -> ExifResult {
     res
 }
 
-/// Parse a byte buffer that should contain a TIFF or JPEG image.
+/// Parse a byte buffer that should contain a TIFF, JPEG, PNG, WebP, HEIF, AVIF, JPEG XL or camera RAW image, or an MP4 or QuickTime video.
 /// Tries to detect format and parse EXIF data.
 ///
 /// Returns warnings alongside result.
+///
+/// An image without EXIF, but with XMP, IPTC, an ICC profile or MPF images, is parsed with no EXIF entries.
 pub fn parse_buffer_quiet(contents: &[u8]) -> (ExifResult, Vec<String>) {
     let mime = detect_type(contents);
     let mut warnings = vec![];
     let (entries, le) = match mime {
         FileType::Unknown => return (Err(ExifError::FileTypeUnknown), warnings),
-        FileType::TIFF => parse_tiff(contents, &mut warnings),
+        FileType::TIFF | FileType::DNG | FileType::CR2 | FileType::NEF | FileType::ARW | FileType::ORF | FileType::RW2 | FileType::PEF => {
+            parse_tiff(contents, &mut warnings)
+        },
         FileType::JPEG => {
             match find_embedded_tiff_in_jpeg(contents).map(|(offset, size)| parse_tiff(&contents[offset..offset + size], &mut warnings)) {
                 Ok(r) => r,
-                Err(e) => return (Err(e), warnings),
+                Err(e) => (Err(e), false),
             }
-        }
+        },
+        FileType::PNG => match find_exif_in_png(contents, &mut warnings) {
+            Ok(tiff) => parse_tiff(&tiff, &mut warnings),
+            Err(e) => (Err(e), false),
+        },
+        FileType::WEBP => match find_exif_in_webp(contents) {
+            Ok(tiff) => parse_tiff(tiff, &mut warnings),
+            Err(e) => (Err(e), false),
+        },
+        FileType::HEIC | FileType::HEIF | FileType::AVIF => match find_exif_in_heif(contents) {
+            Ok(tiff) => parse_tiff(&tiff, &mut warnings),
+            Err(e) => (Err(e), false),
+        },
+        FileType::JXL => match find_exif_in_jxl(contents, &mut warnings) {
+            Ok(tiff) => parse_tiff(&tiff, &mut warnings),
+            Err(e) => (Err(e), false),
+        },
+        FileType::CR3 => parse_cr3(contents, &mut warnings),
+        FileType::MP4 | FileType::MOV => parse_video(contents, &mut warnings),
+        FileType::RAF => match find_exif_in_raf(contents) {
+            Ok(tiff) => parse_tiff(tiff, &mut warnings),
+            Err(e) => (Err(e), false),
+        },
+    };
+    let (xmp, extended_xmp, iptc, icc_profile) = match mime {
+        FileType::JPEG | FileType::RAF => {
+            // RAF has the metadata in its JPEG preview
+            let jpeg = if mime == FileType::RAF { raf_jpeg(contents).unwrap_or_default() } else { contents };
+            let (xmp, extended_xmp) = find_xmp_in_jpeg(jpeg, &mut warnings);
+            let iptc = find_iptc_in_jpeg(jpeg, &mut warnings);
+            (xmp, extended_xmp, iptc, find_icc_in_jpeg(jpeg, &mut warnings))
+        },
+        FileType::PNG => (find_xmp_in_png(contents, &mut warnings), None, Vec::new(), None),
+        FileType::WEBP => (find_xmp_in_webp(contents), None, Vec::new(), find_icc_in_webp(contents)),
+        FileType::HEIC | FileType::HEIF | FileType::AVIF => (find_xmp_in_heif(contents), None, Vec::new(), None),
+        FileType::JXL => (find_xmp_in_jxl(contents, &mut warnings), None, Vec::new(), None),
+        _ => (None, None, Vec::new(), None),
     };
+    let mpf_images = if mime == FileType::JPEG { find_mpf_in_jpeg(contents, &mut warnings) } else { Vec::new() };
 
+    let entries = match entries {
+        Ok(entries) => entries,
+        // the other metadata is still useful without EXIF
+        Err(ExifError::JpegWithoutExif(_) | ExifError::ImageWithoutExif(_))
+            if xmp.is_some() || !iptc.is_empty() || icc_profile.is_some() || !mpf_images.is_empty() => Vec::new(),
+        Err(e) => return (Err(e), warnings),
+    };
     (
-        entries.map(|entries| ExifData {
+        Ok(ExifData {
             mime: mime.as_str(),
+            xmp: xmp.or_else(|| find_xmp_in_entries(&entries)),
+            extended_xmp,
+            iptc,
+            icc_profile: icc_profile.or_else(|| find_icc_in_entries(&entries)),
+            mpf_images,
             entries,
             le,
         }),
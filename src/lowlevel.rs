@@ -47,14 +47,25 @@ pub(crate) fn read_i32(le: bool, raw: &[u8]) -> Option<i32> {
 
 /// Read value from a stream of bytes
 #[inline(always)]
-pub(crate) fn read_f32(raw: &[u8]) -> Option<f32> {
-    raw.get(..4)?.try_into().ok().map(f32::from_le_bytes)
+pub(crate) fn read_u64(le: bool, raw: &[u8]) -> Option<u64> {
+    let bytes = raw.get(..8)?.try_into().ok()?;
+    Some(if le {
+        u64::from_le_bytes(bytes)
+    } else {
+        u64::from_be_bytes(bytes)
+    })
+}
+
+/// Read value from a stream of bytes
+#[inline(always)]
+pub(crate) fn read_f32(le: bool, raw: &[u8]) -> Option<f32> {
+    read_u32(le, raw).map(f32::from_bits)
 }
 
 /// Read value from a stream of bytes
 #[inline(always)]
-pub(crate) fn read_f64(raw: &[u8]) -> Option<f64> {
-    raw.get(..8)?.try_into().ok().map(f64::from_le_bytes)
+pub(crate) fn read_f64(le: bool, raw: &[u8]) -> Option<f64> {
+    read_u64(le, raw).map(f64::from_bits)
 }
 
 /// Read value from a stream of bytes
@@ -114,13 +125,13 @@ pub(crate) fn read_i32_array(le: bool, count: u32, raw: &[u8]) -> Option<Vec<i32
 }
 
 /// Read array from a stream of bytes. Caller must be sure of count and buffer size
-pub(crate) fn read_f32_array(count: u32, raw: &[u8]) -> Option<Vec<f32>> {
-    read_elements(4, count, raw, move |ch| read_f32(ch).unwrap())
+pub(crate) fn read_f32_array(le: bool, count: u32, raw: &[u8]) -> Option<Vec<f32>> {
+    read_elements(4, count, raw, move |ch| read_f32(le, ch).unwrap())
 }
 
 /// Read array from a stream of bytes. Caller must be sure of count and buffer size
-pub(crate) fn read_f64_array(count: u32, raw: &[u8]) -> Option<Vec<f64>> {
-    read_elements(8, count, raw, move |ch| read_f64(ch).unwrap())
+pub(crate) fn read_f64_array(le: bool, count: u32, raw: &[u8]) -> Option<Vec<f64>> {
+    read_elements(8, count, raw, move |ch| read_f64(le, ch).unwrap())
 }
 
 /// Read array from a stream of bytes. Caller must be sure of count and buffer size
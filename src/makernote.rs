@@ -0,0 +1,286 @@
+use super::canon::canon_tag_to_exif;
+use super::exif::{tag_to_exif, ReadableFn};
+use super::fujifilm::fujifilm_tag_to_exif;
+use super::lowlevel::*;
+use super::nikon::nikon_tag_to_exif;
+use super::olympus::*;
+use super::panasonic::panasonic_tag_to_exif;
+use super::pentax::pentax_tag_to_exif;
+use super::sony::sony_tag_to_exif;
+use super::tiff::{parse_exif_entry, parse_ifd};
+use super::types::*;
+use std::ops::Range;
+
+/// Where the manufacturer-specific IFD is inside the `MakerNote` blob, and how to read it
+struct MakernoteLayout {
+    namespace: Namespace,
+    /// Start of the data that offsets within the makernote are relative to
+    base: usize,
+    /// Position of the IFD, relative to `base`
+    ifd_offset: usize,
+    le: bool,
+    /// If true, the offsets are relative to the enclosing TIFF, and break when the makernote is moved
+    fix_base: bool,
+}
+
+/// Same as `tag_to_exif`, but for the tags of the given manufacturer's namespace
+pub(crate) fn makernote_tag_to_exif(namespace: Namespace, f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match namespace {
+        Namespace::Nikon => nikon_tag_to_exif(f),
+        Namespace::Canon => canon_tag_to_exif(f),
+        Namespace::Sony => sony_tag_to_exif(f),
+        Namespace::Fujifilm => fujifilm_tag_to_exif(f),
+        Namespace::Olympus => olympus_tag_to_exif(f),
+        Namespace::OlympusEquipment => olympus_equipment_tag_to_exif(f),
+        Namespace::OlympusCameraSettings => olympus_camera_settings_tag_to_exif(f),
+        Namespace::Panasonic => panasonic_tag_to_exif(f),
+        Namespace::Pentax => pentax_tag_to_exif(f),
+        Namespace::Standard => tag_to_exif(f),
+    }
+}
+
+/// Tags of the makernote IFD that point to further IFDs, and namespaces of these IFDs
+fn makernote_sub_ifds(namespace: Namespace) -> &'static [(u16, Namespace)] {
+    match namespace {
+        Namespace::Olympus => &[(0x2010, Namespace::OlympusEquipment), (0x2020, Namespace::OlympusCameraSettings)],
+        _ => &[],
+    }
+}
+
+/// Byte order mark of the TIFF-like headers used in makernotes
+fn byte_order(mark: &[u8], default: bool) -> bool {
+    match mark {
+        b"II" => true,
+        b"MM" => false,
+        _ => default,
+    }
+}
+
+/// Recognizes the manufacturer by the `Make` tag and the makernote header
+fn detect_layout(make: &str, contents: &[u8], offset: usize, le: bool) -> Option<MakernoteLayout> {
+    let data = contents.get(offset..)?;
+
+    if data.starts_with(b"Nikon\0\x02") {
+        // Contains a complete TIFF header, and offsets are relative to it
+        let base = offset + 10;
+        let tiff = contents.get(base..base + 8)?;
+        let le = match tiff.get(..4)? {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        let ifd_offset = read_u32(le, &tiff[4..])? as usize;
+        return Some(MakernoteLayout { namespace: Namespace::Nikon, base, ifd_offset, le, fix_base: false });
+    }
+    if data.starts_with(b"Nikon\0\x01") {
+        return Some(MakernoteLayout { namespace: Namespace::Nikon, base: 0, ifd_offset: offset + 8, le, fix_base: true });
+    }
+    if make.starts_with("NIKON") {
+        return Some(MakernoteLayout { namespace: Namespace::Nikon, base: 0, ifd_offset: offset, le, fix_base: true });
+    }
+    if make.starts_with("Canon") {
+        return Some(MakernoteLayout { namespace: Namespace::Canon, base: 0, ifd_offset: offset, le, fix_base: true });
+    }
+    if data.starts_with(b"FUJIFILM") {
+        // Always little-endian, with offsets relative to the makernote
+        let ifd_offset = read_u32(true, data.get(8..)?)? as usize;
+        return Some(MakernoteLayout { namespace: Namespace::Fujifilm, base: offset, ifd_offset, le: true, fix_base: false });
+    }
+    if data.starts_with(b"OLYMPUS\0") {
+        let le = byte_order(data.get(8..10)?, le);
+        return Some(MakernoteLayout { namespace: Namespace::Olympus, base: offset, ifd_offset: 12, le, fix_base: false });
+    }
+    if data.starts_with(b"OM SYSTEM\0\0\0") {
+        let le = byte_order(data.get(12..14)?, le);
+        return Some(MakernoteLayout { namespace: Namespace::Olympus, base: offset, ifd_offset: 16, le, fix_base: false });
+    }
+    if data.starts_with(b"OLYMP\0") {
+        return Some(MakernoteLayout { namespace: Namespace::Olympus, base: 0, ifd_offset: offset + 8, le, fix_base: true });
+    }
+    if data.starts_with(b"Panasonic\0\0\0") {
+        return Some(MakernoteLayout { namespace: Namespace::Panasonic, base: 0, ifd_offset: offset + 12, le, fix_base: true });
+    }
+    if data.starts_with(b"PENTAX \0") {
+        let le = byte_order(data.get(8..10)?, le);
+        return Some(MakernoteLayout { namespace: Namespace::Pentax, base: offset, ifd_offset: 10, le, fix_base: false });
+    }
+    if data.starts_with(b"AOC\0") {
+        // Byte order may also be two spaces, which means the same as the enclosing TIFF
+        let le = byte_order(data.get(4..6)?, le);
+        return Some(MakernoteLayout { namespace: Namespace::Pentax, base: 0, ifd_offset: offset + 6, le, fix_base: true });
+    }
+    if data.starts_with(b"SONY DSC \0\0\0") || data.starts_with(b"SONY CAM \0\0\0") {
+        return Some(MakernoteLayout { namespace: Namespace::Sony, base: 0, ifd_offset: offset + 12, le, fix_base: true });
+    }
+    if make.starts_with("SONY") {
+        // Headerless IFD
+        return Some(MakernoteLayout { namespace: Namespace::Sony, base: 0, ifd_offset: offset, le, fix_base: true });
+    }
+    None
+}
+
+/// Makernote values normally follow right after the makernote IFD. If they appear to start
+/// elsewhere, the makernote has been moved by an editor that didn't update its offsets,
+/// and the offsets have to be adjusted by the distance it was moved by.
+fn base_shift(ifd: &[IfdEntry], ifd_end: usize, makernote: &Range<usize>) -> isize {
+    let first_value = ifd.iter()
+        .filter(|e| !e.in_ifd())
+        .filter_map(|e| e.try_data_as_offset())
+        .min();
+
+    let first_value = match first_value {
+        Some(first) if first != ifd_end && first != ifd_end + 4 => first,
+        _ => return 0,
+    };
+
+    // The IFD may or may not be followed by the 4-byte offset of the next IFD
+    [4, 0].iter()
+        .map(|pad| (ifd_end + pad) as isize - first_value as isize)
+        .find(|&shift| {
+            ifd.iter().filter(|e| !e.in_ifd()).all(|e| {
+                e.try_data_as_offset()
+                    .and_then(|o| usize::try_from(o as isize + shift).ok())
+                    .map_or(false, |o| o >= makernote.start && o + e.length() <= makernote.end)
+            })
+        })
+        .unwrap_or(0)
+}
+
+/// Same as `IfdEntry::copy_data`, but with the offset adjusted by `shift`
+fn copy_shifted_data(entry: &mut IfdEntry, contents: &[u8], shift: isize) -> bool {
+    if entry.in_ifd() || shift == 0 {
+        return entry.copy_data(contents);
+    }
+    let offset = match entry.try_data_as_offset().and_then(|o| usize::try_from(o as isize + shift).ok()) {
+        Some(o) => o,
+        None => return false,
+    };
+    if let Some(ext_data) = contents.get(offset..offset + entry.length()) {
+        entry.ext_data.clear();
+        entry.ext_data.extend(ext_data);
+        entry.data = entry.ext_data.clone();
+        return true;
+    }
+    false
+}
+
+/// Parses one IFD of the makernote. Offsets are relative to `base`.
+pub(crate) fn parse_makernote_ifd(
+    base: &[u8],
+    ifd_offset: usize,
+    le: bool,
+    namespace: Namespace,
+    shift: Option<&Range<usize>>,
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) -> Result<(), ExifError> {
+    let count = read_u16(le, base.get(ifd_offset..).ok_or(ExifError::IfdTruncated)?)
+        .ok_or(ExifError::IfdTruncated)?;
+    let ifd_start = ifd_offset + 2;
+    let ifd_end = ifd_start + count as usize * 12;
+    let ifd_content = base.get(ifd_start..ifd_end)
+        .ok_or_else(|| ExifError::ExifIfdTruncated("MakerNote IFD goes past the end of MakerNote".into()))?;
+    let (ifd, _) = parse_ifd(true, le, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
+
+    let shift = match shift {
+        Some(makernote_range) => base_shift(&ifd, ifd_end, makernote_range),
+        None => 0,
+    };
+
+    for mut entry in ifd {
+        entry.namespace = namespace;
+        if !copy_shifted_data(&mut entry, base, shift) {
+            continue;
+        }
+        exif_entries.push(parse_exif_entry(entry, warnings, IfdKind::Makernote));
+    }
+    Ok(())
+}
+
+/// Parses the `MakerNote` of the Exif IFD, if it's in one of the supported formats,
+/// and adds its entries with `IfdKind::Makernote` kind and a manufacturer-specific namespace.
+pub(crate) fn parse_makernote(
+    le: bool,
+    contents: &[u8],
+    exif_entries: &mut Vec<ExifEntry>,
+    warnings: &mut Vec<String>,
+) -> Result<(), ExifError> {
+    let makernote = match exif_entries.iter().find(|e| e.kind == IfdKind::Exif && e.tag == ExifTag::MakerNote) {
+        Some(e) => e,
+        None => return Ok(()),
+    };
+    let make = exif_entries.iter()
+        .find(|e| e.kind == IfdKind::Ifd0 && e.tag == ExifTag::Make)
+        .map(|e| e.value_more_readable.trim())
+        .unwrap_or("");
+
+    let offset = makernote.ifd.try_data_as_offset().ok_or(ExifError::IfdTruncated)?;
+    let makernote_range = offset..offset + makernote.ifd.length();
+    let layout = match detect_layout(make, contents, offset, le) {
+        Some(layout) => layout,
+        None => return Ok(()),
+    };
+
+    // Values outside of the makernote blob would be lost when the makernote is moved, so they're not read
+    let base = contents.get(layout.base..makernote_range.end).ok_or(ExifError::IfdTruncated)?;
+    let shift = if layout.fix_base { Some(&makernote_range) } else { None };
+
+    let first_entry = exif_entries.len();
+    parse_makernote_ifd(base, layout.ifd_offset, layout.le, layout.namespace, shift, exif_entries, warnings)?;
+
+    // Sub-IFDs use the same offset base as the main makernote IFD
+    for &(tag, namespace) in makernote_sub_ifds(layout.namespace) {
+        let sub_ifd_offset = exif_entries[first_entry..].iter()
+            .find(|e| e.ifd.tag == tag)
+            .and_then(|e| e.ifd.try_data_as_offset());
+        if let Some(sub_ifd_offset) = sub_ifd_offset {
+            if let Err(e) = parse_makernote_ifd(base, sub_ifd_offset, layout.le, namespace, None, exif_entries, warnings) {
+                warnings.push(format!("MakerNote sub-IFD {tag:04x} ignored: {e}"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Updates offsets inside a makernote that has been moved to `makernote` position in the `tiff`,
+/// if the makernote uses offsets relative to the TIFF header. Only the main makernote IFD is updated.
+pub(crate) fn fix_makernote_offsets(make: &str, tiff: &mut [u8], makernote: Range<usize>, le: bool) {
+    let layout = match detect_layout(make, tiff, makernote.start, le) {
+        Some(layout) if layout.fix_base => layout,
+        _ => return,
+    };
+
+    let count = match tiff.get(layout.ifd_offset..).and_then(|d| read_u16(layout.le, d)) {
+        Some(count) => count,
+        None => return,
+    };
+    let ifd_start = layout.ifd_offset + 2;
+    let ifd_end = ifd_start + count as usize * 12;
+    let ifd = match tiff.get(ifd_start..ifd_end).and_then(|d| parse_ifd(true, layout.le, count, d)) {
+        Some((ifd, _)) => ifd,
+        None => return,
+    };
+
+    let shift = base_shift(&ifd, ifd_end, &makernote);
+    if shift == 0 {
+        return;
+    }
+
+    for (i, entry) in ifd.iter().enumerate() {
+        if entry.in_ifd() {
+            continue;
+        }
+        let new_offset = match entry.try_data_as_offset().and_then(|o| u32::try_from(o as isize + shift).ok()) {
+            Some(o) => o,
+            None => continue,
+        };
+        let bytes = if layout.le {
+            new_offset.to_le_bytes()
+        } else {
+            new_offset.to_be_bytes()
+        };
+        // The offset is the last field of the 12-byte IFD entry
+        let pos = ifd_start + i * 12 + 8;
+        tiff[pos..pos + 4].copy_from_slice(&bytes);
+    }
+}
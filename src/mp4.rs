@@ -0,0 +1,235 @@
+use super::image::FileType;
+use super::isobmff::*;
+use super::lowlevel::{read_u16, read_u32, read_u64};
+use super::rational::URational;
+use super::raw::parse_cmt_boxes;
+use super::tiff::{add_ifd_link, insert_entry, postprocess_entries, synthetic_entry};
+use super::types::*;
+use super::xmp::exif_date;
+
+/// Seconds between 1904-01-01, the epoch of QuickTime, and 1970-01-01
+const QUICKTIME_EPOCH_OFFSET: i64 = 2_082_844_800;
+
+/// MP4 and QuickTime videos, told apart by the major brand. Old QuickTime files have no `ftyp`.
+pub(crate) fn video_type(contents: &[u8]) -> Option<FileType> {
+    match brands(contents).and_then(|mut brands| brands.next()) {
+        Some(b"qt  ") => Some(FileType::MOV),
+        Some(b"isom" | b"iso2" | b"iso4" | b"iso5" | b"iso6" | b"mp41" | b"mp42" | b"avc1" | b"M4V " | b"3gp4" | b"3gp5" | b"3gp6" | b"3g2a" | b"MSNV" | b"XAVC") => {
+            Some(FileType::MP4)
+        },
+        Some(_) => None,
+        None => {
+            let first = iso_boxes(contents).next()?;
+            let quicktime = matches!(first.kind, b"moov" | b"mdat" | b"wide" | b"free" | b"skip" | b"pnot");
+            if quicktime && find_box(contents, b"moov").is_some() { Some(FileType::MOV) } else { None }
+        },
+    }
+}
+
+/// Metadata of a video, from the most to the least specific source
+#[derive(Default)]
+struct VideoMetadata {
+    /// In EXIF format
+    date: Option<String>,
+    /// Latitude, longitude and altitude
+    location: Option<(f64, f64, Option<f64>)>,
+    make: Option<String>,
+    model: Option<String>,
+}
+
+impl VideoMetadata {
+    fn merge(self, other: Self) -> Self {
+        Self {
+            date: self.date.or(other.date),
+            location: self.location.or(other.location),
+            make: self.make.or(other.make),
+            model: self.model.or(other.model),
+        }
+    }
+}
+
+/// Converts the metadata of the `moov` box to EXIF entries: creation date from `mvhd`,
+/// location, make and model from `udta`, and Apple's `mdta` keys, which take precedence.
+/// Canon's `CMT` boxes are parsed as TIFF, and take precedence over all of them.
+pub(crate) fn parse_video(contents: &[u8], warnings: &mut Vec<String>) -> (ExifEntryResult, bool) {
+    let moov = match find_box(contents, b"moov") {
+        Some(moov) => moov,
+        None => return (Err(ExifError::ImageWithoutExif("Video has no moov box".into())), false),
+    };
+    let (mut exif_entries, le) = match parse_cmt_boxes(contents, warnings) {
+        (Ok(exif_entries), le) => (exif_entries, le),
+        (Err(_), _) => (Vec::new(), false),
+    };
+
+    let metadata = find_box(moov, b"meta").map(mdta_metadata).unwrap_or_default()
+        .merge(find_box(moov, b"udta").map(udta_metadata).unwrap_or_default())
+        .merge(VideoMetadata { date: find_box(moov, b"mvhd").and_then(mvhd_date), ..Default::default() });
+
+    let mut add = |tag: ExifTag, kind: IfdKind, value: TagValue| {
+        if !exif_entries.iter().any(|e| e.tag == tag && e.kind == kind) {
+            let entry = synthetic_entry(tag, kind, &value, le, warnings);
+            insert_entry(&mut exif_entries, entry);
+        }
+    };
+
+    if let Some(make) = metadata.make {
+        add(ExifTag::Make, IfdKind::Ifd0, TagValue::Ascii(make));
+    }
+    if let Some(model) = metadata.model {
+        add(ExifTag::Model, IfdKind::Ifd0, TagValue::Ascii(model));
+    }
+    if let Some(date) = metadata.date {
+        add(ExifTag::DateTimeOriginal, IfdKind::Exif, TagValue::Ascii(date));
+    }
+    if let Some((latitude, longitude, altitude)) = metadata.location {
+        let lat_ref = if latitude < 0. { "S" } else { "N" };
+        let lon_ref = if longitude < 0. { "W" } else { "E" };
+        add(ExifTag::GPSLatitudeRef, IfdKind::Gps, TagValue::Ascii(lat_ref.into()));
+        add(ExifTag::GPSLatitude, IfdKind::Gps, TagValue::URational(dms(latitude)));
+        add(ExifTag::GPSLongitudeRef, IfdKind::Gps, TagValue::Ascii(lon_ref.into()));
+        add(ExifTag::GPSLongitude, IfdKind::Gps, TagValue::URational(dms(longitude)));
+        if let Some(altitude) = altitude {
+            let millimeters = (altitude.abs() * 1000.).round() as u32;
+            add(ExifTag::GPSAltitudeRef, IfdKind::Gps, TagValue::U8(vec![u8::from(altitude < 0.)]));
+            add(ExifTag::GPSAltitude, IfdKind::Gps, TagValue::URational(vec![URational { numerator: millimeters, denominator: 1000 }]));
+        }
+    }
+
+    if exif_entries.is_empty() {
+        return (Err(ExifError::ImageWithoutExif("Video has no metadata".into())), le);
+    }
+    if exif_entries.iter().any(|e| e.kind == IfdKind::Exif) {
+        add_ifd_link(&mut exif_entries, IfdKind::Exif, le, warnings);
+    }
+    if exif_entries.iter().any(|e| e.kind == IfdKind::Gps) {
+        add_ifd_link(&mut exif_entries, IfdKind::Gps, le, warnings);
+    }
+    postprocess_entries(&mut exif_entries);
+    (Ok(exif_entries), le)
+}
+
+/// Creation time, in seconds since 1904 (UTC)
+fn mvhd_date(mvhd: &[u8]) -> Option<String> {
+    let seconds = match *mvhd.first()? {
+        0 => u64::from(read_u32(false, mvhd.get(4..)?)?),
+        1 => read_u64(false, mvhd.get(4..)?)?,
+        _ => return None,
+    };
+    // zero is used when the time is unknown
+    if seconds == 0 {
+        return None;
+    }
+    let unix = i64::try_from(seconds).ok()? - QUICKTIME_EPOCH_OFFSET;
+    let (days, secs) = (unix.div_euclid(86400), unix.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    Some(format!("{year:04}:{month:02}:{day:02} {:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60))
+}
+
+/// Year, month and day of days since 1970-01-01 in the proleptic Gregorian calendar
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+/// QuickTime user data: `©xyz` location, `©mak` and `©mod`
+fn udta_metadata(udta: &[u8]) -> VideoMetadata {
+    VideoMetadata {
+        location: find_box(udta, b"\xa9xyz").and_then(udta_text).and_then(|s| iso6709(&s)),
+        make: find_box(udta, b"\xa9mak").and_then(udta_text),
+        model: find_box(udta, b"\xa9mod").and_then(udta_text),
+        ..Default::default()
+    }
+}
+
+/// Text of the QuickTime user data, after its size and language code
+fn udta_text(data: &[u8]) -> Option<String> {
+    let len = read_u16(false, data)? as usize;
+    let text = data.get(4..4 + len)?;
+    Some(String::from_utf8_lossy(text).trim_end_matches('\0').to_string())
+}
+
+/// Apple's metadata: `keys` box with names, and `ilst` box with values, indexed by the 1-based position of the key
+fn mdta_metadata(meta: &[u8]) -> VideoMetadata {
+    // In QuickTime `meta` isn't a full box, unlike in MP4
+    let meta = if meta.get(4..8) == Some(b"hdlr") { meta } else { meta.get(4..).unwrap_or_default() };
+    let mut metadata = VideoMetadata::default();
+    let keys = match find_box(meta, b"keys").and_then(|keys| keys.get(8..)) {
+        Some(keys) => keys,
+        None => return metadata,
+    };
+    let names: Vec<&[u8]> = iso_boxes(keys).map(|key| key.data).collect();
+
+    for item in iso_boxes(find_box(meta, b"ilst").unwrap_or_default()) {
+        let index = read_u32(false, item.kind).unwrap_or(0) as usize;
+        let name = match index.checked_sub(1).and_then(|i| names.get(i)) {
+            Some(&name) => name,
+            None => continue,
+        };
+        // type indicator 1 is UTF-8, followed by the locale
+        let value = match find_box(item.data, b"data") {
+            Some(data) if read_u32(false, data) == Some(1) => String::from_utf8_lossy(data.get(8..).unwrap_or_default()).into_owned(),
+            _ => continue,
+        };
+        match name {
+            b"com.apple.quicktime.creationdate" => metadata.date = Some(exif_date(&value)),
+            b"com.apple.quicktime.location.ISO6709" => metadata.location = iso6709(&value),
+            b"com.apple.quicktime.make" => metadata.make = Some(value),
+            b"com.apple.quicktime.model" => metadata.model = Some(value),
+            _ => {},
+        }
+    }
+    metadata
+}
+
+/// ISO 6709 location, e.g. "+37.3349-122.0090+030.000/". Degrees may also be written as
+/// "DDMM.mmm" or "DDMMSS.sss", which is told apart by the number of integer digits.
+fn iso6709(s: &str) -> Option<(f64, f64, Option<f64>)> {
+    let s = s.trim().trim_end_matches('/');
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for (i, c) in s.char_indices().skip(1) {
+        if c == '+' || c == '-' {
+            parts.push(&s[start..i]);
+            start = i;
+        }
+    }
+    parts.push(&s[start..]);
+    if parts.len() < 2 || !parts.iter().all(|p| p.starts_with(['+', '-'])) {
+        return None;
+    }
+    let latitude = iso6709_degrees(parts[0], 2)?;
+    let longitude = iso6709_degrees(parts[1], 3)?;
+    let altitude = parts.get(2).and_then(|a| a.parse().ok());
+    Some((latitude, longitude, altitude))
+}
+
+fn iso6709_degrees(s: &str, degree_digits: usize) -> Option<f64> {
+    let value: f64 = s.parse().ok()?;
+    let int_digits = s[1..].split('.').next()?.len();
+    let abs = value.abs();
+    let degrees = match int_digits.checked_sub(degree_digits)? {
+        0 => abs,
+        2 => (abs / 100.).trunc() + (abs % 100.) / 60.,
+        4 => (abs / 10000.).trunc() + (abs / 100. % 100.).trunc() / 60. + (abs % 100.) / 3600.,
+        _ => return None,
+    };
+    Some(degrees.copysign(value))
+}
+
+/// Degrees, minutes and seconds of the absolute value.
+/// Seconds are rounded to milliseconds first, so that they never round up to 60.
+fn dms(degrees: f64) -> Vec<URational> {
+    let millis = (degrees.abs() * 3_600_000.).round() as u32;
+    vec![
+        URational { numerator: millis / 3_600_000, denominator: 1 },
+        URational { numerator: millis / 60_000 % 60, denominator: 1 },
+        URational { numerator: millis % 60_000, denominator: 1000 },
+    ]
+}
@@ -0,0 +1,172 @@
+use super::image::{find_embedded_tiff_in_jpeg, FileType, JpegSegments};
+use super::lowlevel::{read_u16, read_u32};
+use super::tiff::{parse_ifd, parse_tiff, tiff_byte_order};
+use super::types::*;
+use std::fmt::{self, Display};
+
+pub(crate) const MPF_SIGNATURE: &[u8] = b"MPF\0";
+const TAG_MPF_VERSION: u16 = 0xb000;
+const TAG_NUMBER_OF_IMAGES: u16 = 0xb001;
+const TAG_MP_ENTRY: u16 = 0xb002;
+
+/// Type of an image in a Multi-Picture Format file (MPO, Ultra HDR), from its MP entry
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MpfImageType {
+    /// The main image, which is the JPEG file itself
+    Primary,
+    /// Preview of up to 640x480
+    LargeThumbnailVga,
+    /// Preview of up to 1920x1080
+    LargeThumbnailFullHd,
+    Panorama,
+    /// One of the views of a stereo image
+    Disparity,
+    MultiAngle,
+    /// Used by e.g. the gain map of Ultra HDR images
+    Undefined,
+    /// Type code not recognized
+    Other(u32),
+}
+
+impl MpfImageType {
+    fn new(code: u32) -> Self {
+        match code {
+            0x03_0000 => Self::Primary,
+            0x01_0001 => Self::LargeThumbnailVga,
+            0x01_0002 => Self::LargeThumbnailFullHd,
+            0x02_0001 => Self::Panorama,
+            0x02_0002 => Self::Disparity,
+            0x02_0003 => Self::MultiAngle,
+            0x00_0000 => Self::Undefined,
+            code => Self::Other(code),
+        }
+    }
+}
+
+impl Display for MpfImageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Primary => f.write_str("Baseline MP primary image"),
+            Self::LargeThumbnailVga => f.write_str("Large thumbnail (VGA equivalent)"),
+            Self::LargeThumbnailFullHd => f.write_str("Large thumbnail (full HD equivalent)"),
+            Self::Panorama => f.write_str("Multi-frame panorama"),
+            Self::Disparity => f.write_str("Multi-frame disparity"),
+            Self::MultiAngle => f.write_str("Multi-frame multi-angle"),
+            Self::Undefined => f.write_str("Undefined"),
+            Self::Other(code) => write!(f, "Unknown type {code:06x}"),
+        }
+    }
+}
+
+/// An image listed in the MP Index IFD
+#[derive(Debug, PartialEq)]
+pub struct MpfImage {
+    pub image_type: MpfImageType,
+    /// Position of the image's JPEG data in the file
+    pub offset: usize,
+    /// Length of the image's JPEG data
+    pub size: usize,
+    /// EXIF of the image itself, if it has any. The primary image's EXIF is the `ExifData` that has this list.
+    pub exif: Option<ExifData>,
+}
+
+/// Images listed in the MP Index IFD of the APP2 "MPF" segment. Offsets of the images
+/// are relative to the TIFF header of the segment, except the primary image, which is at 0.
+pub(crate) fn find_mpf_in_jpeg(contents: &[u8], warnings: &mut Vec<String>) -> Vec<MpfImage> {
+    let segment = JpegSegments::new(contents).map_while(Result::ok)
+        .find(|s| s.marker == 0xe2 && s.data.starts_with(MPF_SIGNATURE));
+    let segment = match segment {
+        Some(segment) => segment,
+        None => return Vec::new(),
+    };
+    let tiff = &segment.data[MPF_SIGNATURE.len()..];
+    let tiff_start = segment.data_start() + MPF_SIGNATURE.len();
+
+    let entries = match mp_entries(tiff) {
+        Some(entries) => entries,
+        None => {
+            warnings.push("MPF index IFD is malformed".into());
+            return Vec::new();
+        },
+    };
+    if entries.number_of_images.map_or(false, |n| n as usize != entries.entries.len()) {
+        warnings.push("MPF number of images doesn't match the MP entries".into());
+    }
+
+    entries.entries.into_iter().enumerate().map(|(i, (attributes, size, offset))| {
+        let offset = if offset == 0 { 0 } else { tiff_start + offset as usize };
+        let size = size as usize;
+        let exif = if offset == 0 {
+            None
+        } else if let Some(image) = contents.get(offset..offset.saturating_add(size)) {
+            secondary_exif(image, warnings)
+        } else {
+            warnings.push(format!("MPF image {} is out of bounds", i + 1));
+            None
+        };
+        MpfImage { image_type: MpfImageType::new(attributes & 0x00ff_ffff), offset, size, exif }
+    }).collect()
+}
+
+struct MpEntries {
+    number_of_images: Option<u32>,
+    /// Attributes, size and offset of each image
+    entries: Vec<(u32, u32, u32)>,
+}
+
+fn mp_entries(tiff: &[u8]) -> Option<MpEntries> {
+    let le = tiff_byte_order(tiff)?;
+    let ifd_offset = read_u32(le, tiff.get(4..)?)? as usize;
+    let count = read_u16(le, tiff.get(ifd_offset..)?)?;
+    let (mut ifd, _) = parse_ifd(true, le, count, tiff.get(ifd_offset + 2..)?)?;
+    if !ifd.iter().any(|e| e.tag == TAG_MPF_VERSION) {
+        return None;
+    }
+
+    let number_of_images = ifd.iter().find(|e| e.tag == TAG_NUMBER_OF_IMAGES).and_then(|e| read_u32(le, &e.ifd_data));
+    let mp_entry = ifd.iter_mut().find(|e| e.tag == TAG_MP_ENTRY)?;
+    if !mp_entry.copy_data(tiff) {
+        return None;
+    }
+    // attributes, size, offset, and two dependent image entry numbers
+    let entries = mp_entry.data.chunks_exact(16).map(|entry| {
+        Some((read_u32(le, entry)?, read_u32(le, &entry[4..])?, read_u32(le, &entry[8..])?))
+    }).collect::<Option<_>>()?;
+    Some(MpEntries { number_of_images, entries })
+}
+
+/// Replaces the size and offset of each image listed in the MP Index IFD, after the images
+/// have been moved or resized. Returns `None`, leaving the IFD unchanged, if the IFD is malformed
+/// or `update` fails for any of the images.
+pub(crate) fn update_mp_entries(tiff: &mut [u8], mut update: impl FnMut(u32, u32) -> Option<(u32, u32)>) -> Option<()> {
+    let le = tiff_byte_order(tiff)?;
+    let ifd_offset = read_u32(le, tiff.get(4..)?)? as usize;
+    let count = read_u16(le, tiff.get(ifd_offset..)?)?;
+    let (ifd, _) = parse_ifd(true, le, count, tiff.get(ifd_offset + 2..)?)?;
+    let mp_entry = ifd.iter().find(|e| e.tag == TAG_MP_ENTRY)?;
+    let start = mp_entry.try_data_as_offset()?;
+    let entries = tiff.get_mut(start..start.checked_add(mp_entry.length())?)?;
+    let updated = entries.chunks_exact(16)
+        .map(|entry| update(read_u32(le, &entry[4..])?, read_u32(le, &entry[8..])?))
+        .collect::<Option<Vec<_>>>()?;
+    for (entry, (size, offset)) in entries.chunks_exact_mut(16).zip(updated) {
+        entry[4..8].copy_from_slice(&if le { size.to_le_bytes() } else { size.to_be_bytes() });
+        entry[8..12].copy_from_slice(&if le { offset.to_le_bytes() } else { offset.to_be_bytes() });
+    }
+    Some(())
+}
+
+/// Only the EXIF is read, because secondary images can have their own MPF segments
+fn secondary_exif(image: &[u8], warnings: &mut Vec<String>) -> Option<ExifData> {
+    let (offset, size) = find_embedded_tiff_in_jpeg(image).ok()?;
+    let mut image_warnings = Vec::new();
+    let (entries, le) = parse_tiff(&image[offset..offset + size], &mut image_warnings);
+    warnings.extend(image_warnings.into_iter().map(|w| format!("MPF image: {w}")));
+    match entries {
+        Ok(entries) => Some(ExifData::new(FileType::JPEG.as_str(), entries, le)),
+        Err(e) => {
+            warnings.push(format!("MPF image EXIF ignored: {e}"));
+            None
+        },
+    }
+}
@@ -0,0 +1,103 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Nikon `MakerNote` IFD
+pub(crate) fn nikon_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0001 =>
+        (ExifTag::NikonMakerNoteVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x0002 =>
+        (ExifTag::NikonISOSetting, "ISO",
+        IfdFormat::U16, 2, 2, nikon_iso),
+
+        0x0003 =>
+        (ExifTag::NikonColorMode, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0004 =>
+        (ExifTag::NikonQuality, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0005 =>
+        (ExifTag::NikonWhiteBalance, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0006 =>
+        (ExifTag::NikonSharpness, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0007 =>
+        (ExifTag::NikonFocusMode, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0008 =>
+        (ExifTag::NikonFlashSetting, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0009 =>
+        (ExifTag::NikonFlashType, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x001d =>
+        (ExifTag::NikonSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0083 =>
+        (ExifTag::NikonLensType, "none",
+        IfdFormat::U8, 1, 1, nikon_lens_type),
+
+        0x0084 =>
+        (ExifTag::NikonLens, "none",
+        IfdFormat::URational, 4, 4, lens_spec),
+
+        0x0098 =>
+        (ExifTag::NikonLensData, "none",
+        IfdFormat::Undefined, -1i32, -1i32, nikon_lens_data),
+
+        0x00a7 =>
+        (ExifTag::NikonShutterCount, "none",
+        IfdFormat::U32, 1, 1, strpass),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn nikon_iso(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => Some(format!("ISO {}", v.get(1)?).into()),
+        _ => None,
+    }
+}
+
+fn nikon_lens_type(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U8(ref v) => {
+            let flags = *v.first()?;
+            let names = ["MF", "D", "G", "VR", "1", "FT-1", "E", "AF-P"];
+            let set: Vec<_> = names.iter().enumerate()
+                .filter(|&(bit, _)| flags & (1 << bit) != 0)
+                .map(|(_, &name)| name)
+                .collect();
+            Some(if set.is_empty() {
+                "AF".into()
+            } else {
+                set.join(" ").into()
+            })
+        },
+        _ => None,
+    }
+}
+
+/// Only the oldest versions of the lens data are not encrypted
+fn nikon_lens_data(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::Undefined(ref v, _) => {
+            let version = v.get(..4)?;
+            let lens_id_pos = match version {
+                b"0100" => 6,
+                b"0101" => 11,
+                _ => return Some(format!("Encrypted lens data (version {})", String::from_utf8_lossy(version)).into()),
+            };
+            Some(format!("Lens ID number {}", v.get(lens_id_pos)?).into())
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,154 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::ifdformat::NumArray;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the main Olympus `MakerNote` IFD
+pub(crate) fn olympus_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0200 =>
+        (ExifTag::OlympusSpecialMode, "none",
+        IfdFormat::U32, 3, 3, strpass),
+
+        0x0201 =>
+        (ExifTag::OlympusQuality, "none",
+        IfdFormat::U16, 1, 1, olympus_quality),
+
+        0x0202 =>
+        (ExifTag::OlympusMacro, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0x0204 =>
+        (ExifTag::OlympusDigitalZoom, "none",
+        IfdFormat::URational, 1, 1, rational_value),
+
+        0x0207 =>
+        (ExifTag::OlympusCameraType, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0209 =>
+        (ExifTag::OlympusCameraID, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x2010 =>
+        (ExifTag::OlympusEquipment, "byte offset",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+        0x2020 =>
+        (ExifTag::OlympusCameraSettings, "byte offset",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_blob),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+/// Same as `tag_to_exif`, but for the tags of the Olympus Equipment IFD
+pub(crate) fn olympus_equipment_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0000 =>
+        (ExifTag::OlympusEquipmentVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x0100 =>
+        (ExifTag::OlympusCameraType2, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0101 =>
+        (ExifTag::OlympusSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0201 =>
+        (ExifTag::OlympusLensType, "none",
+        IfdFormat::U8, 6, 6, olympus_lens_type),
+
+        0x0202 =>
+        (ExifTag::OlympusLensSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0203 =>
+        (ExifTag::OlympusLensModel, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+/// Same as `tag_to_exif`, but for the tags of the Olympus `CameraSettings` IFD
+pub(crate) fn olympus_camera_settings_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0000 =>
+        (ExifTag::OlympusCameraSettingsVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x0200 =>
+        (ExifTag::OlympusExposureMode, "none",
+        IfdFormat::U16, 1, 1, olympus_exposure_mode),
+
+        0x0301 =>
+        (ExifTag::OlympusFocusMode, "none",
+        IfdFormat::U16, 1, 2, olympus_focus_mode),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn olympus_quality(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "SQ",
+                2 => "HQ",
+                3 => "SHQ",
+                4 => "RAW",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+/// Make, unknown, model, sub-model, unknown, unknown
+fn olympus_lens_type(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U8(ref v) if v.len() >= 4 => {
+            Some(format!("{} {:02x} {:02x}", v[0], v[2], v[3]).into())
+        },
+        TagValue::U8(ref v) => Some(NumArray::new(v).to_string().into()),
+        _ => None,
+    }
+}
+
+fn olympus_exposure_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "Manual",
+                2 => "Program",
+                3 => "Aperture-priority AE",
+                4 => "Shutter speed priority AE",
+                5 => "Program-shift",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn olympus_focus_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                0 => "Single AF",
+                1 => "Sequential shooting AF",
+                2 => "Continuous AF",
+                3 => "Multi AF",
+                4 => "Face detect",
+                10 => "MF",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,93 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Panasonic `MakerNote` IFD
+pub(crate) fn panasonic_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0001 =>
+        (ExifTag::PanasonicImageQuality, "none",
+        IfdFormat::U16, 1, 1, panasonic_image_quality),
+
+        0x0002 =>
+        (ExifTag::PanasonicFirmwareVersion, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_u8),
+
+        0x0003 =>
+        (ExifTag::PanasonicWhiteBalance, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0x0007 =>
+        (ExifTag::PanasonicFocusMode, "none",
+        IfdFormat::U16, 1, 1, panasonic_focus_mode),
+
+        0x001a =>
+        (ExifTag::PanasonicImageStabilization, "none",
+        IfdFormat::U16, 1, 1, panasonic_image_stabilization),
+
+        0x0025 =>
+        (ExifTag::PanasonicInternalSerialNumber, "none",
+        IfdFormat::Undefined, -1i32, -1i32, undefined_as_ascii),
+
+        0x0051 =>
+        (ExifTag::PanasonicLensType, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0x0052 =>
+        (ExifTag::PanasonicLensSerialNumber, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn panasonic_image_quality(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "TIFF",
+                2 => "High",
+                3 => "Normal",
+                6 => "Very High",
+                7 => "RAW",
+                9 => "Motion Picture",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn panasonic_focus_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "Auto",
+                2 => "Manual",
+                4 => "Auto, Focus button",
+                5 => "Auto, Continuous",
+                6 => "AF-S",
+                7 => "AF-C",
+                8 => "AF-F",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn panasonic_image_stabilization(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                2 => "On, Mode 1",
+                3 => "Off",
+                4 => "On, Mode 2",
+                5 => "Panning",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Pentax `MakerNote` IFD
+pub(crate) fn pentax_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0000 =>
+        (ExifTag::PentaxVersion, "none",
+        IfdFormat::U8, 4, 4, pentax_version),
+
+        0x0005 =>
+        (ExifTag::PentaxModelID, "none",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0x0008 =>
+        (ExifTag::PentaxQuality, "none",
+        IfdFormat::U16, 1, 1, pentax_quality),
+
+        0x000d =>
+        (ExifTag::PentaxFocusMode, "none",
+        IfdFormat::U16, 1, 1, pentax_focus_mode),
+
+        0x003f =>
+        (ExifTag::PentaxLensType, "none",
+        IfdFormat::U8, 2, 4, pentax_lens_type),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn pentax_version(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U8(ref v) => Some(v.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(".").into()),
+        _ => None,
+    }
+}
+
+fn pentax_quality(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                0 => "Good",
+                1 => "Better",
+                2 => "Best",
+                3 => "TIFF",
+                4 => "RAW",
+                5 => "Premium",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn pentax_focus_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                0 => "Normal",
+                1 => "Macro",
+                2 => "Infinity",
+                3 => "Manual",
+                4 => "Super Macro",
+                5 => "Pan Focus",
+                16 => "AF-S",
+                17 => "AF-C",
+                18 => "AF-A",
+                32 => "Contrast-detect",
+                33 => "Tracking Contrast-detect",
+                288 => "Face Detect",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+/// Lens series and model number
+fn pentax_lens_type(_tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U8(ref v) => Some(format!("{} {}", v.first()?, v.get(1)?).into()),
+        _ => None,
+    }
+}
@@ -0,0 +1,114 @@
+use super::lowlevel::read_u32;
+use super::types::*;
+use std::borrow::Cow;
+
+pub(crate) const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Compressed text is limited, because a small zlib stream can inflate to gigabytes
+const MAX_INFLATED_SIZE: usize = 1 << 24;
+
+/// A chunk of a PNG file
+pub(crate) struct PngChunk<'a> {
+    pub kind: &'a [u8],
+    pub data: &'a [u8],
+}
+
+/// Iterates over chunks of a PNG file, up to IEND or the first malformed chunk
+pub(crate) fn png_chunks(contents: &[u8]) -> impl Iterator<Item = PngChunk<'_>> {
+    let mut rest = contents.get(PNG_SIGNATURE.len()..).unwrap_or_default();
+    std::iter::from_fn(move || {
+        let len = read_u32(false, rest)? as usize;
+        let kind = rest.get(4..8)?;
+        let data = rest.get(8..8usize.checked_add(len)?)?;
+        // chunk is followed by its CRC
+        rest = rest.get(8 + len + 4..).unwrap_or_default();
+        if kind == b"IEND" {
+            rest = &[];
+        }
+        Some(PngChunk { kind, data })
+    })
+}
+
+/// EXIF from the `eXIf` chunk, or from the ImageMagick-style "Raw profile type exif" text chunk
+pub(crate) fn find_exif_in_png<'a>(contents: &'a [u8], warnings: &mut Vec<String>) -> Result<Cow<'a, [u8]>, ExifError> {
+    let mut raw_profile = None;
+    for chunk in png_chunks(contents) {
+        if chunk.kind == b"eXIf" {
+            // some encoders copy the JPEG APP1 header too
+            return Ok(Cow::Borrowed(chunk.data.strip_prefix(EXIF_HEADER).unwrap_or(chunk.data)));
+        }
+        if raw_profile.is_some() {
+            continue;
+        }
+        if let Some((keyword, text)) = png_text(&chunk, warnings) {
+            if keyword == b"Raw profile type exif" || keyword == b"Raw profile type APP1" {
+                match decode_raw_profile(&text) {
+                    Some(profile) => raw_profile = Some(profile),
+                    None => warnings.push("PNG raw EXIF profile is malformed".into()),
+                }
+            }
+        }
+    }
+
+    match raw_profile {
+        Some(profile) => {
+            let tiff = profile.strip_prefix(EXIF_HEADER).unwrap_or(&profile[..]).to_vec();
+            Ok(Cow::Owned(tiff))
+        },
+        None => Err(ExifError::ImageWithoutExif("PNG has no eXIf chunk".into())),
+    }
+}
+
+/// XMP is stored in an `iTXt` chunk
+pub(crate) fn find_xmp_in_png(contents: &[u8], warnings: &mut Vec<String>) -> Option<Vec<u8>> {
+    png_chunks(contents)
+        .filter(|chunk| chunk.kind == b"iTXt")
+        .filter_map(|chunk| png_text(&chunk, warnings))
+        .find(|(keyword, _)| *keyword == b"XML:com.adobe.xmp")
+        .map(|(_, text)| text.into_owned())
+}
+
+/// Keyword and text of `tEXt`, `zTXt` and `iTXt` chunks
+fn png_text<'a>(chunk: &PngChunk<'a>, warnings: &mut Vec<String>) -> Option<(&'a [u8], Cow<'a, [u8]>)> {
+    let nul = chunk.data.iter().position(|&c| c == 0)?;
+    let keyword = &chunk.data[..nul];
+    let rest = &chunk.data[nul + 1..];
+    let (compressed, text) = match chunk.kind {
+        b"tEXt" => (false, rest),
+        // compression method byte, which can only be 0 (zlib)
+        b"zTXt" => (true, rest.get(1..)?),
+        b"iTXt" => {
+            let compressed = *rest.first()? != 0;
+            // language tag and translated keyword are skipped
+            let mut fields = rest.get(2..)?.splitn(3, |&c| c == 0);
+            fields.next()?;
+            fields.next()?;
+            (compressed, fields.next()?)
+        },
+        _ => return None,
+    };
+    if !compressed {
+        return Some((keyword, Cow::Borrowed(text)));
+    }
+    match miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(text, MAX_INFLATED_SIZE) {
+        Ok(text) => Some((keyword, Cow::Owned(text))),
+        Err(_) => {
+            warnings.push(format!("PNG text chunk {} can't be decompressed", String::from_utf8_lossy(keyword)));
+            None
+        },
+    }
+}
+
+/// ImageMagick's format is "\nexif\n   1234\n45786966...", the profile name, its length, and hex digits
+/// split into lines.
+fn decode_raw_profile(text: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(text).ok()?;
+    let mut lines = text.trim_start().splitn(3, '\n');
+    let _name = lines.next()?;
+    let len: usize = lines.next()?.trim().parse().ok()?;
+    let digits: Vec<u8> = lines.next()?.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    let digits = digits.get(..len.checked_mul(2)?)?;
+    digits.chunks_exact(2).map(|pair| {
+        u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()
+    }).collect()
+}
@@ -0,0 +1,136 @@
+use super::image::{find_embedded_tiff_in_jpeg, FileType};
+use super::isobmff::*;
+use super::lowlevel::{read_u16, read_u32};
+use super::makernote::parse_makernote_ifd;
+use super::tiff::{add_ifd_link, parse_exif_ifd, parse_ifd, postprocess_entries, tiff_byte_order};
+use super::types::*;
+
+const TAG_MAKE: u16 = 0x010f;
+const TAG_COMPRESSION: u16 = 0x0103;
+const TAG_SUB_IFDS: u16 = 0x014a;
+const TAG_DNG_VERSION: u16 = 0xc612;
+
+/// The `uuid` box in `moov` of CR3 files, which contains the metadata boxes
+const CANON_UUID: &[u8] = b"\x85\xc0\xb6\x87\x82\x0f\x11\xe0\x81\x11\xf4\xce\x46\x2b\x6a\x48";
+
+const RAF_SIGNATURE: &[u8] = b"FUJIFILMCCD-RAW ";
+
+/// Tells camera RAW formats apart from plain TIFF files. Olympus and Panasonic have their own
+/// magic numbers, Canon adds a signature after the header, and the rest is told from IFD0.
+pub(crate) fn raw_type(contents: &[u8]) -> Option<FileType> {
+    let le = tiff_byte_order(contents)?;
+    match contents.get(2..4)? {
+        b"RO" | b"RS" | b"OR" => return Some(FileType::ORF),
+        b"U\0" => return Some(FileType::RW2),
+        _ => {},
+    }
+    if contents.get(8..11) == Some(b"CR\x02") {
+        return Some(FileType::CR2);
+    }
+
+    let ifd0 = read_u32(le, contents.get(4..)?)? as usize;
+    let count = read_u16(le, contents.get(ifd0..)?)?;
+    let (mut entries, _) = parse_ifd(true, le, count, contents.get(ifd0 + 2..)?)?;
+    if entries.iter().any(|e| e.tag == TAG_DNG_VERSION) {
+        return Some(FileType::DNG);
+    }
+
+    // TIFFs from scanners and editors may have the camera's make too, but not the raw image data
+    let has_raw_data = entries.iter().any(|e| {
+        e.tag == TAG_SUB_IFDS || (e.tag == TAG_COMPRESSION && read_u16(le, &e.ifd_data).map_or(false, |c| c >= 32767))
+    });
+    if !has_raw_data {
+        return None;
+    }
+    let make = entries.iter_mut().find(|e| e.tag == TAG_MAKE)?;
+    if !make.copy_data(contents) {
+        return None;
+    }
+    match make.data.to_ascii_uppercase() {
+        m if m.starts_with(b"NIKON") => Some(FileType::NEF),
+        m if m.starts_with(b"SONY") => Some(FileType::ARW),
+        m if m.starts_with(b"PENTAX") || m.starts_with(b"RICOH IMAGING") => Some(FileType::PEF),
+        _ => None,
+    }
+}
+
+/// CR3 is an ISOBMFF container with "crx " brand
+pub(crate) fn is_cr3(contents: &[u8]) -> bool {
+    brands(contents).and_then(|mut brands| brands.next()) == Some(b"crx ")
+}
+
+pub(crate) fn is_raf(contents: &[u8]) -> bool {
+    contents.starts_with(RAF_SIGNATURE)
+}
+
+/// Byte order and offset of the first IFD
+fn tiff_header(tiff: &[u8]) -> Option<(bool, usize)> {
+    let le = tiff_byte_order(tiff)?;
+    Some((le, read_u32(le, tiff.get(4..)?)? as usize))
+}
+
+pub(crate) fn parse_cr3(contents: &[u8], warnings: &mut Vec<String>) -> (ExifEntryResult, bool) {
+    let (mut res, le) = parse_cmt_boxes(contents, warnings);
+    if let Ok(exif_entries) = &mut res {
+        postprocess_entries(exif_entries);
+    }
+    (res, le)
+}
+
+/// CR3 keeps IFD0, the Exif IFD, the Canon `MakerNote` and the GPS IFD in `CMT1`-`CMT4` boxes.
+/// Each of them is a separate TIFF structure, so their entries are merged. Canon's MP4 videos have them too.
+pub(crate) fn parse_cmt_boxes(contents: &[u8], warnings: &mut Vec<String>) -> (ExifEntryResult, bool) {
+    let canon_boxes = find_box(contents, b"moov")
+        .and_then(|moov| iso_boxes(moov).find(|b| b.kind == b"uuid" && b.data.starts_with(CANON_UUID)))
+        .map(|b| &b.data[CANON_UUID.len()..]);
+    let cmt1 = canon_boxes.and_then(|boxes| find_box(boxes, b"CMT1"));
+    let (canon_boxes, (le, ifd0)) = match (canon_boxes, cmt1.and_then(tiff_header)) {
+        (Some(boxes), Some(header)) => (boxes, header),
+        _ => return (Err(ExifError::ImageWithoutExif("CR3 has no CMT1 box".into())), false),
+    };
+
+    let mut exif_entries = Vec::new();
+    if let Err(e) = parse_exif_ifd(le, cmt1.unwrap_or_default(), ifd0, &mut exif_entries, warnings, IfdKind::Ifd0) {
+        return (Err(e), le);
+    }
+    for b in iso_boxes(canon_boxes) {
+        let kind = match b.kind {
+            b"CMT2" => IfdKind::Exif,
+            b"CMT3" => IfdKind::Makernote,
+            b"CMT4" => IfdKind::Gps,
+            _ => continue,
+        };
+        let res = match tiff_header(b.data) {
+            Some((le, offset)) if kind == IfdKind::Makernote => {
+                parse_makernote_ifd(b.data, offset, le, Namespace::Canon, None, &mut exif_entries, warnings)
+            },
+            Some((le, offset)) => {
+                let start = exif_entries.len();
+                let res = parse_exif_ifd(le, b.data, offset, &mut exif_entries, warnings, kind);
+                if exif_entries.len() > start {
+                    add_ifd_link(&mut exif_entries, kind, le, warnings);
+                }
+                res
+            },
+            None => Err(ExifError::TiffBadPreamble("CR3 metadata box is not a TIFF".into())),
+        };
+        if let Err(e) = res {
+            warnings.push(format!("CR3 {} box ignored: {e}", String::from_utf8_lossy(b.kind)));
+        }
+    }
+    (Ok(exif_entries), le)
+}
+
+/// RAF starts with a header that has the offset and length of a JPEG preview, which has the EXIF
+pub(crate) fn raf_jpeg(contents: &[u8]) -> Option<&[u8]> {
+    let offset = read_u32(false, contents.get(0x54..)?)? as usize;
+    let length = read_u32(false, contents.get(0x58..)?)? as usize;
+    contents.get(offset..offset.checked_add(length)?)
+}
+
+/// Finds the TIFF in the JPEG preview of RAF
+pub(crate) fn find_exif_in_raf(contents: &[u8]) -> Result<&[u8], ExifError> {
+    let jpeg = raf_jpeg(contents).ok_or_else(|| ExifError::ImageWithoutExif("RAF JPEG preview is out of bounds".into()))?;
+    let (offset, size) = find_embedded_tiff_in_jpeg(jpeg)?;
+    Ok(&jpeg[offset..offset + size])
+}
@@ -0,0 +1,199 @@
+use super::icc::ICC_SIGNATURE;
+use super::image::{FileType, JpegSegments};
+use super::jpeg::{exif_segment, APP0, APP1};
+use super::mpf::{find_mpf_in_jpeg, update_mp_entries, MPF_SIGNATURE};
+use super::tiff::parse_tiff;
+use super::types::*;
+use std::ops::Range;
+
+const APP2: u8 = 0xe2;
+const APP13: u8 = 0xed;
+const APP14: u8 = 0xee;
+const COM: u8 = 0xfe;
+const SOS: u8 = 0xda;
+const TAG_STRIP_OFFSETS: u16 = 0x0111;
+const TAG_TILE_OFFSETS: u16 = 0x0144;
+
+/// Which metadata `ExifData::scrub` and `strip_jpeg_metadata` remove.
+///
+/// XMP and IPTC are removed by every policy, because they can repeat any EXIF value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrubPolicy {
+    /// The GPS IFD
+    Gps,
+    /// Serial numbers, the owner's and artist's names, the unique image ID, and the `MakerNote`
+    DeviceIdentifiers,
+    /// Everything except the orientation and the color information needed to display the image
+    AllButOrientationAndColor,
+    /// All metadata, including the ICC profile
+    All,
+}
+
+impl ExifData {
+    /// Removes the metadata selected by the policy, including from the EXIF of the MPF images.
+    ///
+    /// If IFD0 has image data (TIFF and RAW files), its unrecognized tags and the SubIFDs
+    /// are kept, because they describe the image, e.g. its size and strips.
+    pub fn scrub(&mut self, policy: ScrubPolicy) {
+        let image_data = self.entries.iter().any(|e| e.kind == IfdKind::Ifd0 && matches!(e.ifd.tag, TAG_STRIP_OFFSETS | TAG_TILE_OFFSETS));
+        self.entries.retain(|e| !is_scrubbed(e, policy, image_data));
+        self.unlink_empty_ifds();
+        self.xmp = None;
+        self.extended_xmp = None;
+        self.iptc.clear();
+        if policy == ScrubPolicy::All {
+            self.icc_profile = None;
+        }
+        for exif in self.mpf_images.iter_mut().filter_map(|image| image.exif.as_mut()) {
+            exif.scrub(policy);
+        }
+    }
+}
+
+fn is_scrubbed(entry: &ExifEntry, policy: ScrubPolicy, image_data: bool) -> bool {
+    if image_data && (entry.kind == IfdKind::SubIfd || (entry.kind == IfdKind::Ifd0 && entry.tag == ExifTag::UnknownToMe)) {
+        return false;
+    }
+    // removed with the IFDs they point to
+    if is_ifd_offset(entry.tag) {
+        return false;
+    }
+    if entry.tag == ExifTag::XMP {
+        return true;
+    }
+    match policy {
+        ScrubPolicy::Gps => entry.kind == IfdKind::Gps,
+        ScrubPolicy::DeviceIdentifiers => entry.kind == IfdKind::Makernote || matches!(entry.tag,
+            ExifTag::BodySerialNumber | ExifTag::LensSerialNumber | ExifTag::ImageUniqueID |
+            ExifTag::CameraOwnerName | ExifTag::Artist | ExifTag::MakerNote),
+        ScrubPolicy::AllButOrientationAndColor => !(matches!(entry.kind, IfdKind::Ifd0 | IfdKind::Exif) && matches!(entry.tag,
+            ExifTag::Orientation | ExifTag::InterColorProfile | ExifTag::WhitePoint | ExifTag::PrimaryChromaticities |
+            ExifTag::YCbCrCoefficients | ExifTag::ReferenceBlackWhite | ExifTag::ColorSpace | ExifTag::Gamma)),
+        ScrubPolicy::All => true,
+    }
+}
+
+/// Returns a copy of the JPEG file without the metadata selected by the policy.
+///
+/// The Exif segment is rewritten with `ExifData::scrub`, XMP and IPTC segments are removed,
+/// and `AllButOrientationAndColor` and `All` also remove comments and other application segments,
+/// except JFIF, ICC profile (unless `All`), MPF and Adobe. The entropy-coded data is copied as-is.
+///
+/// Images that follow the primary image in MPO and Ultra HDR files are stripped the same way,
+/// and their sizes and positions are updated in the MPF index.
+pub fn strip_jpeg_metadata(original: &[u8], policy: ScrubPolicy) -> Result<Vec<u8>, ExifError> {
+    if !original.starts_with(&[0xff, 0xd8]) {
+        return Err(ExifError::FileTypeUnknown);
+    }
+    // Start and end of the images that follow the primary image, without overlapping ones
+    let mut secondary: Vec<_> = find_mpf_in_jpeg(original, &mut Vec::new()).iter()
+        .filter(|image| image.offset != 0 && image.offset.saturating_add(image.size) <= original.len())
+        .map(|image| (image.offset, image.offset + image.size))
+        .collect();
+    secondary.sort_unstable();
+    let mut last_end = 0;
+    secondary.retain(|&(start, end)| {
+        let keep = start >= last_end;
+        if keep {
+            last_end = end;
+        }
+        keep
+    });
+
+    let primary_end = secondary.first().map_or(original.len(), |&(start, _)| start);
+    let (mut jpeg, mpf) = strip_image(&original[..primary_end], policy)?;
+    // Start and end of each image in `original`, and in `jpeg`
+    let mut images = vec![(0, primary_end, 0, jpeg.len())];
+    let mut copied = primary_end;
+    for &(start, end) in &secondary {
+        let image = &original[start..end];
+        if !image.starts_with(&[0xff, 0xd8]) {
+            return Err(ExifError::CannotWrite("MPF image is not a JPEG".into()));
+        }
+        jpeg.extend(&original[copied..start]);
+        let new_start = jpeg.len();
+        jpeg.extend(strip_image(image, policy)?.0);
+        images.push((start, end, new_start, jpeg.len()));
+        copied = end;
+    }
+    jpeg.extend(&original[copied..]);
+
+    if let Some(MpfTiff { old_start: old_tiff_start, new: tiff }) = mpf {
+        let tiff_start = tiff.start;
+        update_mp_entries(&mut jpeg[tiff], |size, offset| {
+            // the primary image is at 0
+            let old = if offset == 0 { 0 } else { old_tiff_start + offset as usize };
+            let &(old_start, old_end, new_start, new_end) = images.iter().rev().find(|image| image.0 <= old)?;
+            let (size, new) = if old == old_start {
+                (i64::from(size) + (new_end - new_start) as i64 - (old_end - old_start) as i64, new_start)
+            } else if old < old_end {
+                (i64::from(size), new_start + (old - old_start))
+            } else {
+                (i64::from(size), new_end + (old - old_end))
+            };
+            let offset = if offset == 0 { 0 } else { u32::try_from(new.checked_sub(tiff_start)?).ok()? };
+            Some((u32::try_from(size).ok()?, offset))
+        })
+        .ok_or_else(|| ExifError::CannotWrite("MPF index can't be updated".into()))?;
+    }
+    Ok(jpeg)
+}
+
+/// The TIFF of the MPF segment, in the original image and in the stripped copy
+struct MpfTiff {
+    old_start: usize,
+    new: Range<usize>,
+}
+
+/// Strips the segments of one image, and copies its entropy-coded data and everything after it
+fn strip_image(image: &[u8], policy: ScrubPolicy) -> Result<(Vec<u8>, Option<MpfTiff>), ExifError> {
+    let everything = matches!(policy, ScrubPolicy::AllButOrientationAndColor | ScrubPolicy::All);
+    let mut jpeg = image[..2].to_vec();
+    let mut mpf = None;
+
+    for segment in JpegSegments::new(image) {
+        let segment = segment?;
+        let end = segment.data_start() + segment.data.len();
+        let keep = match segment.marker {
+            APP1 if segment.data.starts_with(EXIF_HEADER) => {
+                if let Some(exif) = scrubbed_exif(&segment.data[EXIF_HEADER.len()..], policy) {
+                    jpeg.extend(exif_segment(&exif, MakernoteOffsets::Fix)?);
+                }
+                continue;
+            },
+            APP1 | APP13 => false,
+            APP0 => !everything || segment.data.starts_with(b"JFIF\0"),
+            APP2 if segment.data.starts_with(ICC_SIGNATURE) => policy != ScrubPolicy::All,
+            APP2 if segment.data.starts_with(MPF_SIGNATURE) => {
+                if mpf.is_none() {
+                    let tiff_start = jpeg.len() + 4 + MPF_SIGNATURE.len();
+                    mpf = Some(MpfTiff {
+                        old_start: segment.data_start() + MPF_SIGNATURE.len(),
+                        new: tiff_start..jpeg.len() + end - segment.start,
+                    });
+                }
+                true
+            },
+            // Adobe's color transform is needed to decode the image
+            APP14 => true,
+            APP2..=0xef | COM => !everything,
+            _ => true,
+        };
+        if keep {
+            jpeg.extend(&image[segment.start..end]);
+        }
+        if segment.marker == SOS {
+            jpeg.extend(&image[end..]);
+            return Ok((jpeg, mpf));
+        }
+    }
+    Err(ExifError::CannotWrite("JPEG has no image data".into()))
+}
+
+/// `None` if nothing is left, or the EXIF can't be parsed
+fn scrubbed_exif(tiff: &[u8], policy: ScrubPolicy) -> Option<ExifData> {
+    let (entries, le) = parse_tiff(tiff, &mut Vec::new());
+    let mut exif = ExifData::new(FileType::JPEG.as_str(), entries.ok()?, le);
+    exif.scrub(policy);
+    if exif.entries.is_empty() { None } else { Some(exif) }
+}
@@ -0,0 +1,77 @@
+use super::exif::ReadableFn;
+use super::exifreadable::*;
+use super::types::*;
+use std::borrow::Cow;
+
+/// Same as `tag_to_exif`, but for the tags of the Sony `MakerNote` IFD
+pub(crate) fn sony_tag_to_exif(f: u16) -> (ExifTag, &'static str, IfdFormat, i32, i32, ReadableFn) {
+    match f {
+        0x0102 =>
+        (ExifTag::SonyQuality, "none",
+        IfdFormat::U32, 1, 1, sony_quality),
+
+        0x0104 =>
+        (ExifTag::SonyFlashExposureComp, "EV",
+        IfdFormat::IRational, 1, 1, rational_value),
+
+        0x0115 =>
+        (ExifTag::SonyWhiteBalance, "none",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0xb001 =>
+        (ExifTag::SonyModelID, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0xb020 =>
+        (ExifTag::SonyCreativeStyle, "none", IfdFormat::Ascii, -1i32, -1i32, strpass),
+
+        0xb027 =>
+        (ExifTag::SonyLensType, "none",
+        IfdFormat::U32, 1, 1, strpass),
+
+        0xb041 =>
+        (ExifTag::SonyExposureMode, "none",
+        IfdFormat::U16, 1, 1, strpass),
+
+        0xb042 =>
+        (ExifTag::SonyFocusMode, "none",
+        IfdFormat::U16, 1, 1, sony_focus_mode),
+
+        _ =>
+        (ExifTag::UnknownToMe, "Unknown unit",
+        IfdFormat::Unknown, -1i32, -1i32, unknown)
+    }
+}
+
+fn sony_quality(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U32(ref v) => {
+            Some(match v.first()? {
+                0 => "RAW",
+                1 => "Super Fine",
+                2 => "Fine",
+                3 => "Standard",
+                4 => "Economy",
+                5 => "Extra Fine",
+                6 => "RAW + JPEG",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
+
+fn sony_focus_mode(tag: u16, e: &TagValue) -> Option<Cow<'static, str>> {
+    match *e {
+        TagValue::U16(ref v) => {
+            Some(match v.first()? {
+                1 => "AF-S",
+                2 => "AF-C",
+                4 => "Permanent-AF",
+                65535 => "n/a",
+                n => return Some(format!("Unknown ({tag:04x}={n})").into()),
+            }.into())
+        },
+        _ => None,
+    }
+}
@@ -2,16 +2,24 @@ use super::exif::*;
 use super::exifpost::*;
 use super::ifdformat::*;
 use super::lowlevel::*;
+use super::makernote::*;
 use super::types::*;
 use std::borrow::Cow;
 
 type InExifResult = Result<(), ExifError>;
 
+/// Limit for broken or malicious files with looping or huge `SubIFDs` lists
+const MAX_SUB_IFDS: usize = 64;
+
 /// Parse of raw IFD entry into EXIF data, if it is of a known type, and returns
 /// an `ExifEntry` object. If the tag is unknown, the enumeration is set to `UnknownToMe`,
 /// but the raw information of tag is still available in the ifd member.
 pub(crate) fn parse_exif_entry(ifd: IfdEntry, warnings: &mut Vec<String>, kind: IfdKind) -> ExifEntry {
-    let (tag, unit, format, min_count, max_count, more_readable) = tag_to_exif(ifd.tag);
+    let (tag, unit, format, min_count, max_count, more_readable) = match (ifd.namespace, kind) {
+        (Namespace::Standard, IfdKind::Interoperability) => interop_tag_to_exif(ifd.tag),
+        (Namespace::Standard, _) => tag_to_exif(ifd.tag),
+        (namespace, _) => makernote_tag_to_exif(namespace, ifd.tag),
+    };
     let value = match tag_value_new(&ifd) {
         Some(v) => v,
         None => TagValue::Invalid(ifd.data.clone(), ifd.le, ifd.format as u16, ifd.count),
@@ -36,7 +44,7 @@ pub(crate) fn parse_exif_entry(ifd: IfdEntry, warnings: &mut Vec<String>, kind:
     // 1) tag must match enum
     // 2) all types except Ascii, Undefined, Unknown must have definite length
     // 3) Str type must not have a definite length
-    if (((tag as u32) & 0xffff) as u16) != e.ifd.tag
+    if tag.code() != e.ifd.tag
         || (min_count == -1
             && (format != IfdFormat::Ascii
                 && format != IfdFormat::Undefined
@@ -103,8 +111,8 @@ pub fn parse_ifd(
     Some((entries, next_ifd))
 }
 
-/// Deep parse of IFD that grabs EXIF data from IFD0, `SubIFD` and GPS IFD
-fn parse_exif_ifd(
+/// Deep parse of IFD that grabs EXIF data from IFD0, `SubIFD`, GPS IFD, Interoperability IFD or IFD1
+pub(crate) fn parse_exif_ifd(
     le: bool,
     contents: &[u8],
     ioffset: usize,
@@ -150,7 +158,29 @@ fn parse_exif_ifd(
     Ok(())
 }
 
-/// Parses IFD0 and looks for `SubIFD` or GPS IFD within IFD0
+/// Copies the JPEG thumbnail pointed to by IFD1 into the `ext_data` of its `JPEGInterchangeFormat` entry
+fn load_thumbnail(ifd1: &mut [ExifEntry], contents: &[u8]) {
+    let length = ifd1.iter()
+        .find(|e| e.tag == ExifTag::JPEGInterchangeFormatLength)
+        .and_then(|e| e.value.to_i64(0));
+
+    if let (Some(length), Some(entry)) = (length, ifd1.iter_mut().find(|e| e.tag == ExifTag::JPEGInterchangeFormat)) {
+        let offset = entry.value.to_i64(0).unwrap_or(0) as usize;
+        if let Some(thumbnail) = contents.get(offset..offset.saturating_add(length as usize)) {
+            entry.ifd.ext_data = thumbnail.to_vec();
+        }
+    }
+}
+
+/// Offsets of IFDs listed in a `SubIFDs` entry
+fn sub_ifd_offsets(entry: &ExifEntry) -> Vec<usize> {
+    match &entry.value {
+        TagValue::U32(offsets) if entry.tag == ExifTag::SubIFDs => offsets.iter().map(|&o| o as usize).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses IFD0, looks for `SubIFD` or GPS IFD within IFD0, follows `SubIFDs`, and follows the link to IFD1
 pub fn parse_ifds(
     le: bool,
     ifd0_offset: usize,
@@ -182,13 +212,13 @@ pub fn parse_ifds(
     let ifd_content = &contents
         .get(offset..offset + ifd_length)
         .ok_or(ExifError::IfdTruncated)?;
-    let (ifd, _) = parse_ifd(false, le, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
+    let (ifd, next_ifd) = parse_ifd(false, le, count, ifd_content).ok_or(ExifError::IfdTruncated)?;
 
     for entry in &ifd {
         // Identify which IFD this entry belongs to (IFD-0, Exif, Gps, IFD-1 etc)
-        let ifd_kind = if entry.tag == (((ExifTag::ExifOffset as u32) & 0xffff) as u16) {
+        let ifd_kind = if entry.tag == ExifTag::ExifOffset.code() {
             IfdKind::Exif
-        } else if entry.tag == (((ExifTag::GPSOffset as u32) & 0xffff) as u16) {
+        } else if entry.tag == ExifTag::GPSOffset.code() {
             // Gps
             IfdKind::Gps
         } else {
@@ -202,32 +232,145 @@ pub fn parse_ifds(
         parse_exif_ifd(le, contents, exif_offset, &mut exif_entries, warnings, ifd_kind)?;
     }
 
+    // The Exif IFD may in turn point to the Interoperability IFD. A broken one is not fatal.
+    let interop_offset = exif_entries.iter()
+        .find(|e| e.kind == IfdKind::Exif && e.tag == ExifTag::InteropOffset)
+        .and_then(|e| e.ifd.try_data_as_offset());
+    if let Some(interop_offset) = interop_offset {
+        if let Err(e) = parse_exif_ifd(le, contents, interop_offset, &mut exif_entries, warnings, IfdKind::Interoperability) {
+            warnings.push(format!("Interoperability IFD ignored: {e}"));
+        }
+    }
+
+    // RAW files keep the full-size image in SubIFDs, which may have SubIFDs of their own.
+    // A broken one is not fatal.
+    let mut pending: Vec<usize> = exif_entries.iter().filter(|e| e.kind == IfdKind::Ifd0).flat_map(sub_ifd_offsets).collect();
+    let mut visited = vec![ifd0_offset];
+    while let Some(sub_ifd) = pending.pop() {
+        if visited.contains(&sub_ifd) || visited.len() > MAX_SUB_IFDS {
+            continue;
+        }
+        visited.push(sub_ifd);
+        let start = exif_entries.len();
+        match parse_exif_ifd(le, contents, sub_ifd, &mut exif_entries, warnings, IfdKind::SubIfd) {
+            Ok(()) => pending.extend(exif_entries[start..].iter().flat_map(sub_ifd_offsets)),
+            Err(e) => warnings.push(format!("SubIFD ignored: {e}")),
+        }
+    }
+
+    // IFD0 links to IFD1, which describes the thumbnail. A broken IFD1 is not fatal,
+    // since the main image metadata has been read already.
+    if next_ifd != 0 && next_ifd != ifd0_offset {
+        let ifd1_start = exif_entries.len();
+        match parse_exif_ifd(le, contents, next_ifd, &mut exif_entries, warnings, IfdKind::Ifd1) {
+            Ok(()) => load_thumbnail(&mut exif_entries[ifd1_start..], contents),
+            Err(e) => warnings.push(format!("IFD1 ignored: {e}")),
+        }
+    }
+
+    if let Err(e) = parse_makernote(le, contents, &mut exif_entries, warnings) {
+        warnings.push(format!("MakerNote ignored: {e}"));
+    }
+
+    postprocess_entries(&mut exif_entries);
+    Ok(exif_entries)
+}
+
+/// Creates an entry for a value that didn't come from an IFD, e.g. from a video container
+pub(crate) fn synthetic_entry(tag: ExifTag, kind: IfdKind, value: &TagValue, le: bool, warnings: &mut Vec<String>) -> ExifEntry {
+    let ifd = IfdEntry::from_value(tag.code(), value, le);
+    parse_exif_entry(ifd, warnings, kind)
+}
+
+/// Inserts the entry after the entries of the same IFD, keeping them sorted by tag
+pub(crate) fn insert_entry(exif_entries: &mut Vec<ExifEntry>, entry: ExifEntry) {
+    let pos = match exif_entries.iter().position(|e| e.kind == entry.kind) {
+        Some(start) => exif_entries[start..].iter()
+            .position(|e| e.kind != entry.kind || e.ifd.tag > entry.ifd.tag)
+            .map_or(exif_entries.len(), |p| start + p),
+        None => exif_entries.len(),
+    };
+    exif_entries.insert(pos, entry);
+}
+
+/// Adds the entry that points to the Exif or GPS IFD from IFD0, or to the Interoperability IFD
+/// from the Exif IFD, for containers that store these IFDs separately. The entries are needed to serialize them.
+pub(crate) fn add_ifd_link(exif_entries: &mut Vec<ExifEntry>, kind: IfdKind, le: bool, warnings: &mut Vec<String>) {
+    let (tag, parent) = match kind {
+        IfdKind::Exif => (ExifTag::ExifOffset, IfdKind::Ifd0),
+        IfdKind::Gps => (ExifTag::GPSOffset, IfdKind::Ifd0),
+        IfdKind::Interoperability => (ExifTag::InteropOffset, IfdKind::Exif),
+        _ => return,
+    };
+    if exif_entries.iter().any(|e| e.kind == parent && e.tag == tag) {
+        return;
+    }
+    // the offset is set when serializing
+    let entry = synthetic_entry(tag, parent, &TagValue::U32(vec![0]), le, warnings);
+    insert_entry(exif_entries, entry);
+    add_ifd_link(exif_entries, parent, le, warnings);
+}
+
+/// Fills in values of entries that depend on other entries
+pub(crate) fn postprocess_entries(exif_entries: &mut [ExifEntry]) {
     for n in 0..exif_entries.len() {
         let (begin, end) = exif_entries.split_at_mut(n);
         let (entry, end) = end.split_first_mut().unwrap();
         exif_postprocessing(entry, begin, end);
     }
+}
 
-    Ok(exif_entries)
+/// Returns `true` for little-endian TIFF, `false` for big-endian, and `None` if it's not a TIFF header.
+/// Olympus ORF and Panasonic RW2 use their own magic numbers instead of 42.
+pub(crate) fn tiff_byte_order(contents: &[u8]) -> Option<bool> {
+    match contents.get(..4)? {
+        b"II*\0" | b"IIRO" | b"IIRS" | b"IIU\0" => Some(true),
+        b"MM\0*" | b"MMOR" => Some(false),
+        _ => None,
+    }
 }
 
 /// Parse a TIFF image, or embedded TIFF in JPEG, in order to get IFDs and then the EXIF data
 pub fn parse_tiff(contents: &[u8], warnings: &mut Vec<String>) -> (ExifEntryResult, bool) {
-    let mut le = false;
-
     if contents.len() < 8 {
         return (Err(ExifError::TiffTruncated), false);
-    } else if contents[0] == b'I' && contents[1] == b'I' && contents[2] == 42 && contents[3] == 0 {
-        /* TIFF little-endian */
-        le = true;
-    } else if contents[0] == b'M' && contents[1] == b'M' && contents[2] == 0 && contents[3] == 42 {
-        /* TIFF big-endian */
-    } else {
-        let err = format!("Preamble is {:x} {:x} {:x} {:x}", contents[0], contents[1], contents[2], contents[3]);
-        return (Err(ExifError::TiffBadPreamble(err)), false);
     }
+    let le = match tiff_byte_order(contents) {
+        Some(le) => le,
+        None => {
+            let err = format!("Preamble is {:x} {:x} {:x} {:x}", contents[0], contents[1], contents[2], contents[3]);
+            return (Err(ExifError::TiffBadPreamble(err)), false);
+        },
+    };
 
     let offset = read_u32(le, &contents[4..]).unwrap() as usize;
 
     (parse_ifds(le, offset, contents, warnings), le)
 }
+
+/// Returns a copy of the TIFF file with IFD-0 and its Exif, GPS and Interoperability IFDs
+/// replaced by the entries of `exif`. This also works for camera RAW files based on TIFF.
+///
+/// The original bytes are kept as they are, and the new IFDs are appended at the end of the file,
+/// so strips, tiles, SubIFDs and the following pages stay valid. The IFD-1 entries of `exif`
+/// are not written, because IFD-0 keeps linking to the original next page.
+pub fn write_exif_to_tiff(original: &[u8], exif: &ExifData) -> Result<Vec<u8>, ExifError> {
+    let le = tiff_byte_order(original).ok_or(ExifError::FileTypeUnknown)?;
+    if le != exif.le {
+        return Err(ExifError::CannotWrite("EXIF byte order doesn't match the TIFF file".into()));
+    }
+    let ifd0 = read_u32(le, &original[4..]).ok_or(ExifError::TiffTruncated)? as usize;
+    let count = original.get(ifd0..).and_then(|ifd| read_u16(le, ifd)).ok_or(ExifError::TiffTruncated)? as usize;
+    let next_ifd = original.get(ifd0 + 2 + count * 12..).and_then(|next| read_u32(le, next)).ok_or(ExifError::TiffTruncated)?;
+
+    let mut tiff = original.to_vec();
+    // IFDs must begin on a word boundary
+    if tiff.len() % 2 == 1 {
+        tiff.push(0);
+    }
+    exif.serialize_ifds(&mut tiff, MakernoteOffsets::Fix, Some(next_ifd))?;
+    if u32::try_from(tiff.len()).is_err() {
+        return Err(ExifError::CannotWrite("TIFF file would be larger than 4GB".into()));
+    }
+    Ok(tiff)
+}
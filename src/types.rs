@@ -1,5 +1,12 @@
+use super::exif::tag_ifd_kind;
 use super::ifdformat::tag_value_eq;
+use super::makernote::fix_makernote_offsets;
 use super::rational::{IRational, URational};
+use super::icc::IccHeader;
+use super::iptc::{IptcEntry, IptcTag, IptcValue};
+use super::mpf::MpfImage;
+use super::tiff::{add_ifd_link, insert_entry, postprocess_entries, synthetic_entry};
+use super::xmp::{xmp_tag_value, Xmp};
 use std::borrow::Cow;
 use std::{fmt, io};
 
@@ -9,22 +16,193 @@ const INTEL_TIFF_HEADER: &[u8] = &[b'I', b'I', 0x2a, 0x00];
 const MOTOROLA_TIFF_HEADER: &[u8] = &[b'M', b'M', 0x00, 0x2a];
 const DATA_WIDTH: usize = 4;
 
-/// Top-level structure that contains all parsed metadata inside an image
-#[derive(Debug, PartialEq)]
+/// Top-level structure that contains all parsed metadata inside an image.
+///
+/// XMP, IPTC, the ICC profile and the MPF images are read with accessors, because they are stored outside of the EXIF.
+/// Two `ExifData` are equal if their EXIF is equal.
+#[derive(Debug)]
 pub struct ExifData {
-    /// MIME type of the parsed image. It may be "image/jpeg", "image/tiff", or empty if unrecognized.
+    /// MIME type of the parsed image. It may be "image/jpeg", "image/tiff", "image/png", "image/webp",
+    /// "image/heic", "image/heif", "image/avif", "image/jxl", a RAW type like "image/x-adobe-dng",
+    /// "video/mp4", "video/quicktime", or empty if unrecognized.
     pub mime: &'static str,
     /// Collection of EXIF entries found in the image
     pub entries: Vec<ExifEntry>,
     /// If `true`, this uses little-endian byte ordering for the raw bytes. Otherwise, it uses big-endian ordering.
     pub le: bool,
+    pub(crate) xmp: Option<Vec<u8>>,
+    pub(crate) extended_xmp: Option<Vec<u8>>,
+    pub(crate) iptc: Vec<IptcEntry>,
+    pub(crate) icc_profile: Option<Vec<u8>>,
+    pub(crate) mpf_images: Vec<MpfImage>,
+}
+
+impl PartialEq for ExifData {
+    fn eq(&self, other: &Self) -> bool {
+        self.mime == other.mime && self.entries == other.entries && self.le == other.le
+    }
 }
 
 impl ExifData {
     #[must_use]
     pub fn new(mime: &'static str, entries: Vec<ExifEntry>, le: bool) -> Self {
-        Self { mime, entries, le }
+        Self { mime, entries, le, xmp: None, extended_xmp: None, iptc: Vec::new(), icc_profile: None, mpf_images: Vec::new() }
+    }
+
+    /// The raw XMP packet (XML), if the image has one. It's from the JPEG APP1 segment, the TIFF `XMP` tag,
+    /// the PNG and WebP chunks, the HEIF item, or the JPEG XL box.
+    #[must_use]
+    pub fn xmp(&self) -> Option<&[u8]> {
+        self.xmp.as_deref()
+    }
+
+    /// The raw ExtendedXMP packet, reassembled from the JPEG APP1 chunks. It holds properties
+    /// that didn't fit in the main XMP packet.
+    #[must_use]
+    pub fn extended_xmp(&self) -> Option<&[u8]> {
+        self.extended_xmp.as_deref()
+    }
+
+    /// IPTC-IIM datasets, from the Photoshop resources in JPEG's APP13 segments
+    #[must_use]
+    pub fn iptc(&self) -> &[IptcEntry] {
+        &self.iptc
+    }
+
+    /// ICC color profile, reassembled from JPEG's APP2 segments, or from the TIFF `InterColorProfile` tag,
+    /// or the WebP `ICCP` chunk
+    #[must_use]
+    pub fn icc_profile(&self) -> Option<&[u8]> {
+        self.icc_profile.as_deref()
+    }
+
+    /// Images of an MPO or Ultra HDR JPEG, from the Multi-Picture Format index in APP2, including the primary image
+    #[must_use]
+    pub fn mpf_images(&self) -> &[MpfImage] {
+        &self.mpf_images
+    }
+
+    /// Header of the ICC color profile, if the image has a valid one
+    #[must_use]
+    pub fn icc_header(&self) -> Option<IccHeader> {
+        IccHeader::parse(self.icc_profile.as_deref()?)
+    }
+
+    /// Values of an IPTC dataset. Most datasets have one value, but e.g. `Keywords` can have many.
+    pub fn iptc_values(&self, tag: IptcTag) -> impl Iterator<Item = &IptcValue> {
+        self.iptc.iter().filter(move |e| e.tag == tag).map(|e| &e.value)
+    }
+
+    /// Properties of the XMP and ExtendedXMP packets, if the image has XMP
+    #[must_use]
+    pub fn parse_xmp(&self) -> Option<Xmp> {
+        let mut xmp = Xmp::parse(self.xmp.as_deref()?);
+        if let Some(extended_xmp) = &self.extended_xmp {
+            xmp.parse_packet(extended_xmp);
+        }
+        Some(xmp)
+    }
+
+    /// Value of the tag from the IFD where the tag belongs, or if there's no such entry, from the XMP properties.
+    ///
+    /// XMP values are converted to the format of the EXIF tag (e.g. "28/10" to a `URational`,
+    /// and ISO 8601 dates to the EXIF date format). Values that can't be converted are returned as `TagValue::Ascii`.
+    #[must_use]
+    pub fn effective_value(&self, tag: ExifTag) -> Option<Cow<'_, TagValue>> {
+        if let Some(value) = self.get(tag) {
+            return Some(Cow::Borrowed(value));
+        }
+        xmp_tag_value(&self.parse_xmp()?, tag).map(Cow::Owned)
+    }
+
+    /// The JPEG thumbnail embedded in IFD1, if the image has one.
+    ///
+    /// These are the bytes pointed to by `JPEGInterchangeFormat` and `JPEGInterchangeFormatLength`.
+    #[must_use]
+    pub fn thumbnail(&self) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|e| e.kind == IfdKind::Ifd1 && e.tag == ExifTag::JPEGInterchangeFormat)
+            .map(|e| &e.ifd.ext_data[..])
+            .filter(|thumb| !thumb.is_empty())
+    }
+
+    /// Value of the tag, from the IFD where the tag belongs (e.g. not the resolution of the thumbnail in IFD1)
+    #[must_use]
+    pub fn get(&self, tag: ExifTag) -> Option<&TagValue> {
+        let kind = tag_ifd_kind(tag)?;
+        self.entries.iter().find(|e| e.kind == kind && e.tag == tag).map(|e| &e.value)
+    }
+
+    /// Adds the tag, or replaces its value. The entry is placed in the IFD where the tag belongs,
+    /// and the Exif, GPS or Interoperability IFD is linked if it didn't exist.
+    ///
+    /// The value must have the format and count expected by the tag. Offsets to other IFDs,
+    /// and tags of unknown or `MakerNote` IFDs can't be set.
+    /// The value is encoded in the byte order of `le` with [`IfdEntry::from_value`].
+    pub fn set(&mut self, tag: ExifTag, value: TagValue) -> Result<(), ExifError> {
+        let kind = match tag_ifd_kind(tag) {
+            Some(kind) if kind != IfdKind::Makernote && kind != IfdKind::Ifd1 && !is_ifd_offset(tag) => kind,
+            _ => return Err(ExifError::CannotWrite(format!("{tag} can't be set"))),
+        };
+        let mut warnings = Vec::new();
+        let entry = synthetic_entry(tag, kind, &value, self.le, &mut warnings);
+        if let Some(warning) = warnings.pop() {
+            return Err(ExifError::CannotWrite(warning));
+        }
+
+        match self.entries.iter().position(|e| e.kind == kind && e.tag == tag) {
+            Some(pos) => self.entries[pos] = entry,
+            None => insert_entry(&mut self.entries, entry),
+        }
+        add_ifd_link(&mut self.entries, kind, self.le, &mut warnings);
+        postprocess_entries(&mut self.entries);
+        Ok(())
     }
+
+    /// Removes the tag from the IFD where it belongs, and returns its value. The links to
+    /// Exif, GPS and Interoperability IFDs that have become empty are removed too.
+    ///
+    /// Offsets to other IFDs can't be removed, and are left as they are.
+    pub fn remove(&mut self, tag: ExifTag) -> Option<TagValue> {
+        let kind = tag_ifd_kind(tag).filter(|_| !is_ifd_offset(tag))?;
+        let pos = self.entries.iter().position(|e| e.kind == kind && e.tag == tag)?;
+        let removed = self.entries.remove(pos);
+        self.unlink_empty_ifds();
+        postprocess_entries(&mut self.entries);
+        Some(removed.value)
+    }
+
+    /// Removes the offsets to Exif, GPS and Interoperability IFDs that have no entries
+    pub(crate) fn unlink_empty_ifds(&mut self) {
+        // The Interoperability IFD is linked from the Exif IFD, so it goes first
+        for (kind, parent, link) in [
+            (IfdKind::Interoperability, IfdKind::Exif, ExifTag::InteropOffset),
+            (IfdKind::Exif, IfdKind::Ifd0, ExifTag::ExifOffset),
+            (IfdKind::Gps, IfdKind::Ifd0, ExifTag::GPSOffset),
+        ] {
+            if !self.entries.iter().any(|e| e.kind == kind) {
+                self.entries.retain(|e| !(e.kind == parent && e.tag == link));
+            }
+        }
+    }
+}
+
+/// Tags whose values are offsets that are set when serializing
+pub(crate) fn is_ifd_offset(tag: ExifTag) -> bool {
+    matches!(tag, ExifTag::ExifOffset | ExifTag::GPSOffset | ExifTag::InteropOffset | ExifTag::SubIFDs)
+}
+
+/// What `ExifData::serialize_with` does with the offsets inside the `MakerNote` blob
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MakernoteOffsets {
+    /// Copy the blob unchanged. Makernotes that use offsets relative to the TIFF header
+    /// will point to wrong data after the blob is moved to a new position.
+    Unchanged,
+    /// Adjust offsets inside the makernote IFD by the distance the blob has been moved by
+    /// (the same way exiftool does it). Makernotes that use offsets relative to themselves
+    /// are left unchanged.
+    Fix,
 }
 
 impl ExifData {
@@ -32,7 +210,15 @@ impl ExifData {
     ///
     /// *Note*: this serializes the metadata according to its original endianness (specified
     /// through the `le` attribute).
+    ///
+    /// The `MakerNote` is copied as-is. See `serialize_with` for keeping it readable.
     pub fn serialize(&self) -> Result<Vec<u8>, ExifError> {
+        self.serialize_with(MakernoteOffsets::Unchanged)
+    }
+
+    /// Same as `serialize`, but can also update offsets inside the `MakerNote`, so that editing
+    /// other tags doesn't corrupt the manufacturer-specific data.
+    pub fn serialize_with(&self, makernote_offsets: MakernoteOffsets) -> Result<Vec<u8>, ExifError> {
         // Select the right TIFF header based on the endianness.
         let tiff_header = if self.le {
             INTEL_TIFF_HEADER
@@ -46,36 +232,63 @@ impl ExifData {
         // Generate the TIFF header.
         serialized.extend(tiff_header);
 
-        // The offset to IFD-0. IFD-0 follows immediately after the TIFF header.
-        // The offset is a 4-byte value - serialize it to bytes:
-        let offset = if self.le {
-            (tiff_header.len() as u32 + std::mem::size_of::<u32>() as u32).to_le_bytes()
+        // The offset to IFD-0, filled in when IFD-0 is written immediately after the TIFF header.
+        serialized.extend(&[0, 0, 0, 0]);
+
+        self.serialize_ifds(&mut serialized, makernote_offsets, None)?;
+
+        Ok(if self.mime == "image/jpeg" {
+            [EXIF_HEADER, &serialized].concat()
         } else {
-            (tiff_header.len() as u32 + std::mem::size_of::<u32>() as u32).to_be_bytes()
+            serialized
+        })
+    }
+
+    /// Append IFD-0 and the IFDs linked from it to `serialized`, which starts with a TIFF header,
+    /// and point the header to the new IFD-0.
+    ///
+    /// If `next_ifd` is set, the IFDs are added to an existing TIFF file: IFD-0 links to the
+    /// original next IFD instead of the IFD-1 entries, and SubIFDs are kept, because they still
+    /// point to the original data.
+    pub(crate) fn serialize_ifds(
+        &self,
+        serialized: &mut Vec<u8>,
+        makernote_offsets: MakernoteOffsets,
+        next_ifd: Option<u32>,
+    ) -> Result<(), ExifError> {
+        let bytes = if self.le {
+            (serialized.len() as u32).to_le_bytes()
+        } else {
+            (serialized.len() as u32).to_be_bytes()
         };
-        serialized.extend(&offset);
+        for (place, byte) in serialized.iter_mut().skip(4).zip(bytes.iter()) {
+            *place = *byte;
+        }
 
         let mut ifd0 = vec![];
         let mut ifd1 = vec![];
         let mut exif = vec![];
         let mut gps = vec![];
+        let mut interop = vec![];
 
+        let has_thumbnail = self.thumbnail().is_some();
         for e in &self.entries {
             match e.kind {
+                // SubIFDs aren't written, so the offsets would be dangling
+                IfdKind::Ifd0 if e.tag == ExifTag::SubIFDs && next_ifd.is_none() => {},
                 IfdKind::Ifd0 => ifd0.push(e),
+                // Without the thumbnail data, the offset would point to whatever is written there
+                IfdKind::Ifd1 if !has_thumbnail && matches!(e.tag, ExifTag::JPEGInterchangeFormat | ExifTag::JPEGInterchangeFormatLength) => {},
                 IfdKind::Ifd1 => ifd1.push(e),
                 IfdKind::Exif => exif.push(e),
                 IfdKind::Gps => gps.push(e),
-                _ => {
-                    // XXX Silently ignore Makernote and Interoperability IFDs
+                IfdKind::Interoperability => interop.push(e),
+                IfdKind::Makernote | IfdKind::SubIfd => {
+                    // XXX Silently ignore Makernote IFDs and SubIFDs
                 },
             }
         }
 
-        // IFD-1 contains the thumbnail. For now, the parser discards IFD-1, so its serialization
-        // has not yet been implemented.
-        assert!(ifd1.is_empty());
-
         // Serialize the number of directory entries in this IFD.
         if self.le {
             serialized.extend(&(ifd0.len() as u16).to_le_bytes());
@@ -93,7 +306,7 @@ impl ExifData {
         // These offsets will be filled out (patched) later.
         let mut data_patches = vec![];
         for entry in ifd0 {
-            entry.ifd.serialize(&mut serialized, &mut data_patches)?;
+            entry.ifd.serialize(serialized, &mut data_patches)?;
 
             // If IFD-0 points to an Exif/GPS sub-IFD, the offset of the sub-IFD must be serialized
             // inside IFD-0. Subtract `DATA_WIDTH` from the length, because the pointer to the
@@ -108,13 +321,10 @@ impl ExifData {
             }
         }
 
-        if ifd1.is_empty() {
-            serialized.extend(&[0, 0, 0, 0]);
-        } else {
-            // Otherwise, serialize the pointer to IFD-1 (which is just the offset of IFD-1 in the
-            // file).
-            unimplemented!("IFD-1");
-        }
+        // The position of the pointer to IFD-1 (which is just the offset of IFD-1 in the file).
+        // It stays zero if there is no IFD-1.
+        let ifd1_pointer = serialized.len();
+        serialized.extend(&[0, 0, 0, 0]);
 
         // Patch the offsets serialized above.
         for patch in &data_patches {
@@ -131,30 +341,54 @@ impl ExifData {
             }
         }
 
+        // The Interoperability IFD is linked from the Exif IFD
+        let mut interop_ifd_pointer = None;
+
+        // Makernote layout depends on the camera manufacturer
+        let make = match makernote_offsets {
+            MakernoteOffsets::Fix => Some(self.entries.iter()
+                .find(|e| e.kind == IfdKind::Ifd0 && e.tag == ExifTag::Make)
+                .map_or("", |e| e.value_more_readable.trim())),
+            MakernoteOffsets::Unchanged => None,
+        };
+
         if !exif.is_empty() {
-            self.serialize_ifd(&mut serialized, exif, exif_ifd_pointer)?;
+            interop_ifd_pointer = self.serialize_ifd(serialized, exif, exif_ifd_pointer, make)?;
+        }
+
+        if !interop.is_empty() {
+            self.serialize_ifd(serialized, interop, interop_ifd_pointer, None)?;
         }
 
         if !gps.is_empty() {
-            self.serialize_ifd(&mut serialized, gps, gps_ifd_pointer)?;
+            self.serialize_ifd(serialized, gps, gps_ifd_pointer, None)?;
         }
 
-        // TODO Makernote, Interoperability IFD, Thumbnail image
+        // IFD-1 contains the thumbnail, and is linked from the end of IFD-0
+        if let Some(next_ifd) = next_ifd {
+            let bytes = if self.le { next_ifd.to_le_bytes() } else { next_ifd.to_be_bytes() };
+            serialized[ifd1_pointer..ifd1_pointer + 4].copy_from_slice(&bytes);
+        } else if !ifd1.is_empty() {
+            self.serialize_ifd(serialized, ifd1, Some(ifd1_pointer), None)?;
+        }
 
-        Ok(if self.mime == "image/jpeg" {
-            [EXIF_HEADER, &serialized].concat()
-        } else {
-            serialized
-        })
+        // TODO Makernote
+
+        Ok(())
     }
 
-    /// Serialize GPS/Exif IFD entries.
+    /// Serialize GPS/Exif/Interoperability/IFD-1 entries.
+    ///
+    /// If `make` is set, offsets inside the `MakerNote` are fixed up for its new position.
+    ///
+    /// Returns the position of the Interoperability IFD pointer, if there was an `InteropOffset` entry.
     fn serialize_ifd(
         &self,
         serialized: &mut Vec<u8>,
         entries: Vec<&ExifEntry>,
         pos: Option<usize>,
-    ) -> Result<(), ExifError> {
+        make: Option<&str>,
+    ) -> Result<Option<usize>, ExifError> {
         let bytes = if self.le {
             (serialized.len() as u32).to_le_bytes()
         } else {
@@ -168,32 +402,54 @@ impl ExifData {
             serialized.extend(&(entries.len() as u16).to_be_bytes());
         }
 
-        // Write the offset of this IFD in IFD-0.
+        // Write the offset of this IFD in IFD-0 (or in the IFD-0 next-IFD link).
         let pos = pos.ok_or(ExifError::MissingExifOffset)?;
         for (place, byte) in serialized.iter_mut().skip(pos).zip(bytes.iter()) {
             *place = *byte;
         }
 
         let mut data_patches = vec![];
+        let mut interop_ifd_pointer = None;
+        // Index of the patch that writes the makernote
+        let mut makernote_patch = None;
 
         for entry in entries {
             entry.ifd.serialize(serialized, &mut data_patches)?;
+
+            if entry.tag == ExifTag::MakerNote && !entry.ifd.in_ifd() {
+                makernote_patch = Some(data_patches.len() - 1);
+            }
+
+            if entry.tag == ExifTag::InteropOffset {
+                interop_ifd_pointer = Some(serialized.len() - DATA_WIDTH);
+            }
+
+            // The thumbnail is not the value of the `JPEGInterchangeFormat` entry, but is pointed
+            // to by it, so it's written to the data section like any other out-of-IFD value.
+            if entry.tag == ExifTag::JPEGInterchangeFormat && !entry.ifd.ext_data.is_empty() {
+                data_patches.push(Patch::new((serialized.len() - DATA_WIDTH) as u32, &entry.ifd.ext_data));
+            }
         }
 
         serialized.extend(&[0, 0, 0, 0]);
-        for patch in &data_patches {
+        for (i, patch) in data_patches.iter().enumerate() {
             // The position of the data pointed to by the IFD entries serialized above.
+            let data_pos = serialized.len();
             let bytes = if self.le {
-                (serialized.len() as u32).to_le_bytes()
+                (data_pos as u32).to_le_bytes()
             } else {
-                (serialized.len() as u32).to_be_bytes()
+                (data_pos as u32).to_be_bytes()
             };
             serialized.extend(patch.data);
             for (place, byte) in serialized.iter_mut().skip(patch.offset_pos as usize).zip(bytes.iter()) {
                 *place = *byte;
             }
+
+            if let (Some(make), true) = (make, makernote_patch == Some(i)) {
+                fix_makernote_offsets(make, serialized, data_pos..data_pos + patch.data.len(), self.le);
+            }
         }
-        Ok(())
+        Ok(interop_ifd_pointer)
     }
 }
 
@@ -206,7 +462,7 @@ pub(super) struct Patch<'a> {
 
 impl Patch<'_> {
     #[must_use]
-    pub const fn new(offset_pos: u32, data: &[u8]) -> Patch {
+    pub const fn new(offset_pos: u32, data: &[u8]) -> Patch<'_> {
         Patch {
             offset_pos,
             data,
@@ -220,6 +476,10 @@ pub enum ExifError {
     IoError(io::Error),
     FileTypeUnknown,
     JpegWithoutExif(String),
+    /// An image in other format than JPEG or TIFF, that has no EXIF metadata
+    ImageWithoutExif(String),
+    /// The image can't be modified to contain the metadata
+    CannotWrite(String),
     TiffTruncated,
     TiffBadPreamble(String),
     IfdTruncated,
@@ -320,13 +580,21 @@ impl IfdEntry {
 }
 
 /// Enumeration that represent EXIF tag namespaces. Namespaces exist to
-/// accomodate future parsing of the manufacturer-specific tags embedded within
-/// the `MarkerNote` tag.
+/// accomodate the manufacturer-specific tags embedded within the `MarkerNote` tag.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Namespace {
     Standard = 0x0000,
     Nikon = 0x0001,
     Canon = 0x0002,
+    Sony = 0x0003,
+    Fujifilm = 0x0004,
+    Olympus = 0x0005,
+    /// Equipment sub-IFD of the Olympus makernote
+    OlympusEquipment = 0x0006,
+    /// Camera settings sub-IFD of the Olympus makernote
+    OlympusCameraSettings = 0x0007,
+    Panasonic = 0x0008,
+    Pentax = 0x0009,
 }
 
 /// Enumeration that represents recognized EXIF tags found in TIFF IFDs.
@@ -337,8 +605,11 @@ pub enum Namespace {
 ///
 /// On the other hand, the namespace code is arbitrary, it only matches
 /// the `Namespace` enumeration. The namespace is 0 for standard Exif tags.
-/// The non-standard namespaces exist to accomodate future parsing of the
-/// `MarkerNote` tag, that contains embedded manufacturer-specific tags.
+/// The non-standard namespaces accomodate the tags of the `MarkerNote` tag,
+/// that contains embedded manufacturer-specific tags.
+///
+/// Tags of the Interoperability IFD whose codes clash with GPS tags can't use their code,
+/// and are told apart by the IFD they are found in (`IfdKind::Interoperability`).
 #[derive(Copy, Clone, Debug, PartialEq, Hash)]
 #[repr(u32)]
 pub enum ExifTag {
@@ -354,14 +625,21 @@ pub enum ExifTag {
     ResolutionUnit = 0x0000_0128,
     Software = 0x0000_0131,
     DateTime = 0x0000_0132,
+    Artist = 0x0000_013b,
     HostComputer = 0x0000_013c,
     WhitePoint = 0x0000_013e,
     PrimaryChromaticities = 0x0000_013f,
     YCbCrCoefficients = 0x0000_0211,
     ReferenceBlackWhite = 0x0000_0214,
     Copyright = 0x0000_8298,
+    XMP = 0x0000_02bc,
+    InterColorProfile = 0x0000_8773,
+    SubIFDs = 0x0000_014a,
     ExifOffset = 0x0000_8769,
     GPSOffset = 0x0000_8825,
+    JPEGInterchangeFormat = 0x0000_0201,
+    JPEGInterchangeFormatLength = 0x0000_0202,
+    InteropOffset = 0x0000_a005,
 
     ExposureTime = 0x0000_829a,
     FNumber = 0x0000_829d,
@@ -412,9 +690,12 @@ pub enum ExifTag {
     DeviceSettingDescription = 0x0000_a40b,
     SubjectDistanceRange = 0x0000_a40c,
     ImageUniqueID = 0x0000_a420,
+    CameraOwnerName = 0x0000_a430,
+    BodySerialNumber = 0x0000_a431,
     LensSpecification = 0x0000_a432,
     LensMake = 0x0000_a433,
     LensModel = 0x0000_a434,
+    LensSerialNumber = 0x0000_a435,
     Gamma = 0xa500,
 
     GPSVersionID = 0x00000,
@@ -448,10 +729,111 @@ pub enum ExifTag {
     GPSAreaInformation = 0x0000_001c,
     GPSDateStamp = 0x0000_001d,
     GPSDifferential = 0x0000_001e,
+
+    /// Tag code 0x0001 of the Interoperability IFD
+    InteroperabilityIndex = 0x0000_fff1,
+    /// Tag code 0x0002 of the Interoperability IFD
+    InteroperabilityVersion = 0x0000_fff2,
+    RelatedImageWidth = 0x0000_1001,
+    RelatedImageLength = 0x0000_1002,
+
+    NikonMakerNoteVersion = 0x0001_0001,
+    NikonISOSetting = 0x0001_0002,
+    NikonColorMode = 0x0001_0003,
+    NikonQuality = 0x0001_0004,
+    NikonWhiteBalance = 0x0001_0005,
+    NikonSharpness = 0x0001_0006,
+    NikonFocusMode = 0x0001_0007,
+    NikonFlashSetting = 0x0001_0008,
+    NikonFlashType = 0x0001_0009,
+    NikonSerialNumber = 0x0001_001d,
+    NikonLensType = 0x0001_0083,
+    NikonLens = 0x0001_0084,
+    NikonLensData = 0x0001_0098,
+    NikonShutterCount = 0x0001_00a7,
+
+    CanonCameraSettings = 0x0002_0001,
+    CanonFocalLength = 0x0002_0002,
+    CanonShotInfo = 0x0002_0004,
+    CanonImageType = 0x0002_0006,
+    CanonFirmwareVersion = 0x0002_0007,
+    CanonFileNumber = 0x0002_0008,
+    CanonOwnerName = 0x0002_0009,
+    CanonSerialNumber = 0x0002_000c,
+    CanonModelID = 0x0002_0010,
+    CanonLensModel = 0x0002_0095,
+    CanonInternalSerialNumber = 0x0002_0096,
+
+    SonyQuality = 0x0003_0102,
+    SonyFlashExposureComp = 0x0003_0104,
+    SonyWhiteBalance = 0x0003_0115,
+    SonyModelID = 0x0003_b001,
+    SonyCreativeStyle = 0x0003_b020,
+    SonyLensType = 0x0003_b027,
+    SonyExposureMode = 0x0003_b041,
+    SonyFocusMode = 0x0003_b042,
+
+    FujifilmVersion = 0x0004_0000,
+    FujifilmInternalSerialNumber = 0x0004_0010,
+    FujifilmQuality = 0x0004_1000,
+    FujifilmSharpness = 0x0004_1001,
+    FujifilmWhiteBalance = 0x0004_1002,
+    FujifilmFocusMode = 0x0004_1021,
+    FujifilmPictureMode = 0x0004_1031,
+    FujifilmDynamicRange = 0x0004_1400,
+    FujifilmMinFocalLength = 0x0004_1404,
+    FujifilmMaxFocalLength = 0x0004_1405,
+    FujifilmImageCount = 0x0004_1438,
+
+    OlympusSpecialMode = 0x0005_0200,
+    OlympusQuality = 0x0005_0201,
+    OlympusMacro = 0x0005_0202,
+    OlympusDigitalZoom = 0x0005_0204,
+    OlympusCameraType = 0x0005_0207,
+    OlympusCameraID = 0x0005_0209,
+    OlympusEquipment = 0x0005_2010,
+    OlympusCameraSettings = 0x0005_2020,
+
+    OlympusEquipmentVersion = 0x0006_0000,
+    OlympusCameraType2 = 0x0006_0100,
+    OlympusSerialNumber = 0x0006_0101,
+    OlympusLensType = 0x0006_0201,
+    OlympusLensSerialNumber = 0x0006_0202,
+    OlympusLensModel = 0x0006_0203,
+
+    OlympusCameraSettingsVersion = 0x0007_0000,
+    OlympusExposureMode = 0x0007_0200,
+    OlympusFocusMode = 0x0007_0301,
+
+    PanasonicImageQuality = 0x0008_0001,
+    PanasonicFirmwareVersion = 0x0008_0002,
+    PanasonicWhiteBalance = 0x0008_0003,
+    PanasonicFocusMode = 0x0008_0007,
+    PanasonicImageStabilization = 0x0008_001a,
+    PanasonicInternalSerialNumber = 0x0008_0025,
+    PanasonicLensType = 0x0008_0051,
+    PanasonicLensSerialNumber = 0x0008_0052,
+
+    PentaxVersion = 0x0009_0000,
+    PentaxModelID = 0x0009_0005,
+    PentaxQuality = 0x0009_0008,
+    PentaxFocusMode = 0x0009_000d,
+    PentaxLensType = 0x0009_003f,
 }
 
 impl Eq for ExifTag {}
 
+impl ExifTag {
+    /// The code of the tag in its IFD
+    pub(crate) fn code(self) -> u16 {
+        match self {
+            Self::InteroperabilityIndex => 0x0001,
+            Self::InteroperabilityVersion => 0x0002,
+            tag => (tag as u32 & 0xffff) as u16,
+        }
+    }
+}
+
 impl fmt::Display for ExifTag {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -473,8 +855,15 @@ impl fmt::Display for ExifTag {
                 ExifTag::YCbCrCoefficients => "YCbCr Coefficients",
                 ExifTag::ReferenceBlackWhite => "Reference Black/White",
                 ExifTag::Copyright => "Copyright",
+                ExifTag::Artist => "Artist",
+                ExifTag::XMP => "XMP metadata",
+                ExifTag::InterColorProfile => "ICC profile",
+                ExifTag::SubIFDs => "This image has SubIFDs",
                 ExifTag::ExifOffset => "This image has an Exif SubIFD",
                 ExifTag::GPSOffset => "This image has a GPS SubIFD",
+                ExifTag::JPEGInterchangeFormat => "Thumbnail offset",
+                ExifTag::JPEGInterchangeFormatLength => "Thumbnail length",
+                ExifTag::InteropOffset => "This image has an Interoperability IFD",
                 ExifTag::ExposureTime => "Exposure time",
                 ExifTag::SensitivityType => "Sensitivity type",
                 ExifTag::FNumber => "Aperture",
@@ -524,6 +913,9 @@ impl fmt::Display for ExifTag {
                 ExifTag::LensSpecification => "Lens specification",
                 ExifTag::LensMake => "Lens manufacturer",
                 ExifTag::LensModel => "Lens model",
+                ExifTag::LensSerialNumber => "Lens serial number",
+                ExifTag::CameraOwnerName => "Camera owner name",
+                ExifTag::BodySerialNumber => "Camera serial number",
                 ExifTag::Gamma => "Gamma",
                 ExifTag::DeviceSettingDescription => "Device setting description",
                 ExifTag::SubjectDistanceRange => "Subject distance range",
@@ -559,6 +951,84 @@ impl fmt::Display for ExifTag {
                 ExifTag::GPSAreaInformation => "GPS area information",
                 ExifTag::GPSDateStamp => "GPS date stamp",
                 ExifTag::GPSDifferential => "GPS differential",
+                ExifTag::InteroperabilityIndex => "Interoperability index",
+                ExifTag::InteroperabilityVersion => "Interoperability version",
+                ExifTag::RelatedImageWidth => "Related image width",
+                ExifTag::RelatedImageLength => "Related image length",
+                ExifTag::NikonMakerNoteVersion => "Nikon maker note version",
+                ExifTag::NikonISOSetting => "Nikon ISO setting",
+                ExifTag::NikonColorMode => "Nikon color mode",
+                ExifTag::NikonQuality => "Nikon quality",
+                ExifTag::NikonWhiteBalance => "Nikon white balance",
+                ExifTag::NikonSharpness => "Nikon sharpness",
+                ExifTag::NikonFocusMode => "Nikon focus mode",
+                ExifTag::NikonFlashSetting => "Nikon flash setting",
+                ExifTag::NikonFlashType => "Nikon flash type",
+                ExifTag::NikonSerialNumber => "Nikon serial number",
+                ExifTag::NikonLensType => "Nikon lens type",
+                ExifTag::NikonLens => "Nikon lens",
+                ExifTag::NikonLensData => "Nikon lens data",
+                ExifTag::NikonShutterCount => "Nikon shutter count",
+                ExifTag::CanonCameraSettings => "Canon camera settings",
+                ExifTag::CanonFocalLength => "Canon focal length",
+                ExifTag::CanonShotInfo => "Canon shot info",
+                ExifTag::CanonImageType => "Canon image type",
+                ExifTag::CanonFirmwareVersion => "Canon firmware version",
+                ExifTag::CanonFileNumber => "Canon file number",
+                ExifTag::CanonOwnerName => "Canon owner name",
+                ExifTag::CanonSerialNumber => "Canon serial number",
+                ExifTag::CanonModelID => "Canon model ID",
+                ExifTag::CanonLensModel => "Canon lens model",
+                ExifTag::CanonInternalSerialNumber => "Canon internal serial number",
+                ExifTag::SonyQuality => "Sony quality",
+                ExifTag::SonyFlashExposureComp => "Sony flash exposure compensation",
+                ExifTag::SonyWhiteBalance => "Sony white balance",
+                ExifTag::SonyModelID => "Sony model ID",
+                ExifTag::SonyCreativeStyle => "Sony creative style",
+                ExifTag::SonyLensType => "Sony lens type",
+                ExifTag::SonyExposureMode => "Sony exposure mode",
+                ExifTag::SonyFocusMode => "Sony focus mode",
+                ExifTag::FujifilmVersion => "Fujifilm maker note version",
+                ExifTag::FujifilmInternalSerialNumber => "Fujifilm internal serial number",
+                ExifTag::FujifilmQuality => "Fujifilm quality",
+                ExifTag::FujifilmSharpness => "Fujifilm sharpness",
+                ExifTag::FujifilmWhiteBalance => "Fujifilm white balance",
+                ExifTag::FujifilmFocusMode => "Fujifilm focus mode",
+                ExifTag::FujifilmPictureMode => "Fujifilm picture mode",
+                ExifTag::FujifilmDynamicRange => "Fujifilm dynamic range",
+                ExifTag::FujifilmMinFocalLength => "Fujifilm min focal length",
+                ExifTag::FujifilmMaxFocalLength => "Fujifilm max focal length",
+                ExifTag::FujifilmImageCount => "Fujifilm image count",
+                ExifTag::OlympusSpecialMode => "Olympus special mode",
+                ExifTag::OlympusQuality => "Olympus quality",
+                ExifTag::OlympusMacro => "Olympus macro",
+                ExifTag::OlympusDigitalZoom => "Olympus digital zoom",
+                ExifTag::OlympusCameraType => "Olympus camera type",
+                ExifTag::OlympusCameraID => "Olympus camera ID",
+                ExifTag::OlympusEquipment => "Olympus equipment IFD",
+                ExifTag::OlympusCameraSettings => "Olympus camera settings IFD",
+                ExifTag::OlympusEquipmentVersion => "Olympus equipment version",
+                ExifTag::OlympusCameraType2 => "Olympus camera type",
+                ExifTag::OlympusSerialNumber => "Olympus serial number",
+                ExifTag::OlympusLensType => "Olympus lens type",
+                ExifTag::OlympusLensSerialNumber => "Olympus lens serial number",
+                ExifTag::OlympusLensModel => "Olympus lens model",
+                ExifTag::OlympusCameraSettingsVersion => "Olympus camera settings version",
+                ExifTag::OlympusExposureMode => "Olympus exposure mode",
+                ExifTag::OlympusFocusMode => "Olympus focus mode",
+                ExifTag::PanasonicImageQuality => "Panasonic image quality",
+                ExifTag::PanasonicFirmwareVersion => "Panasonic firmware version",
+                ExifTag::PanasonicWhiteBalance => "Panasonic white balance",
+                ExifTag::PanasonicFocusMode => "Panasonic focus mode",
+                ExifTag::PanasonicImageStabilization => "Panasonic image stabilization",
+                ExifTag::PanasonicInternalSerialNumber => "Panasonic internal serial number",
+                ExifTag::PanasonicLensType => "Panasonic lens type",
+                ExifTag::PanasonicLensSerialNumber => "Panasonic lens serial number",
+                ExifTag::PentaxVersion => "Pentax maker note version",
+                ExifTag::PentaxModelID => "Pentax model ID",
+                ExifTag::PentaxQuality => "Pentax quality",
+                ExifTag::PentaxFocusMode => "Pentax focus mode",
+                ExifTag::PentaxLensType => "Pentax lens type",
                 ExifTag::UnknownToMe => "Unknown to this library, or manufacturer-specific",
             }
         )
@@ -621,10 +1091,11 @@ pub struct ExifEntry {
 
 impl PartialEq for ExifEntry {
     fn eq(&self, other: &Self) -> bool {
-        // If the ExifEntry is an ExifOffset or a GPSOffset, the value it contains is an offset.
-        // Two entries can be equal even if they do not point to the same offset.
+        // If the ExifEntry is an ExifOffset, a GPSOffset, an InteropOffset or a thumbnail pointer,
+        // the value it contains is an offset. Two entries can be equal even if they do not point
+        // to the same offset.
         let value_eq = match self.tag {
-            ExifTag::ExifOffset | ExifTag::GPSOffset => true,
+            ExifTag::ExifOffset | ExifTag::GPSOffset | ExifTag::InteropOffset | ExifTag::JPEGInterchangeFormat => true,
             _ => {
                 self.value_more_readable == other.value_more_readable && tag_value_eq(&self.value, &other.value)
             },
@@ -709,7 +1180,7 @@ impl TagValue {
             TagValue::I16(ref v) => v.get(index).copied().map(From::from),
             TagValue::I32(ref v) => v.get(index).copied().map(From::from),
             TagValue::F32(ref v) => v.get(index).copied().map(From::from),
-            TagValue::F64(ref v) => v.get(index).copied().map(From::from),
+            TagValue::F64(ref v) => v.get(index).copied(),
             TagValue::IRational(ref v) => v.get(index).copied().map(|v| v.value()),
             TagValue::URational(ref v) => v.get(index).copied().map(|v| v.value()),
             _ => None,
@@ -731,4 +1202,6 @@ pub enum IfdKind {
     Gps,
     Makernote,
     Interoperability,
+    /// IFDs of RAW files linked from the `SubIFDs` tag, e.g. the full-size image
+    SubIfd,
 }
@@ -1,6 +1,6 @@
 use super::lowlevel::*;
 use super::types::*;
-use crate::ifdformat::NumArray;
+use crate::ifdformat::{tag_value_encode, NumArray};
 use std::error::Error;
 use std::fmt::Display;
 use std::{fmt, io};
@@ -13,14 +13,15 @@ pub fn ifdformat_new(n: u16) -> IfdFormat {
 }
 
 impl IfdFormat {
-    /// Convert an IFD format code to the `IfdFormat` enumeration
+    /// Convert an IFD format code to the `IfdFormat` enumeration.
+    /// The IFD type (13) of TIFF 6.0 Supplement 1 is a 32-bit offset, so it's read as `U32`.
     #[must_use]
     pub const fn new(code: u16) -> Self {
         match code {
             1 => Self::U8,
             2 => Self::Ascii,
             3 => Self::U16,
-            4 => Self::U32,
+            4 | 13 => Self::U32,
             5 => Self::URational,
             6 => Self::I8,
             7 => Self::Undefined,
@@ -35,6 +36,33 @@ impl IfdFormat {
 }
 
 impl IfdEntry {
+    /// Encodes the value as an entry of a standard IFD, in the given byte order.
+    /// Strings get a NUL terminator. Data that doesn't fit within the IFD structure is
+    /// kept in `ext_data`, and its offset is written when serializing.
+    #[must_use]
+    pub fn from_value(tag: u16, value: &TagValue, le: bool) -> Self {
+        let (format, count, data) = tag_value_encode(value, le);
+        let mut entry = Self {
+            namespace: Namespace::Standard,
+            tag,
+            format,
+            count,
+            data: Vec::new(),
+            ifd_data: vec![0; 4],
+            ext_data: Vec::new(),
+            le,
+        };
+        if entry.in_ifd() {
+            let len = data.len().min(4);
+            entry.ifd_data[..len].copy_from_slice(&data[..len]);
+            entry.data = entry.ifd_data.clone();
+        } else {
+            entry.ext_data = data.clone();
+            entry.data = data;
+        }
+        entry
+    }
+
     #[deprecated]
     #[must_use]
     pub fn data_as_offset(&self) -> usize {
@@ -124,6 +152,8 @@ impl Display for ExifError {
             ExifError::IoError(ref e) => e.fmt(f),
             ExifError::FileTypeUnknown => f.write_str("File type unknown"),
             ExifError::JpegWithoutExif(ref s) => write!(f, "JPEG without EXIF section: {s}"),
+            ExifError::ImageWithoutExif(ref s) => write!(f, "Image without EXIF: {s}"),
+            ExifError::CannotWrite(ref s) => write!(f, "Can't write metadata: {s}"),
             ExifError::TiffTruncated => f.write_str("TIFF truncated at start"),
             ExifError::TiffBadPreamble(ref s) => write!(f, "TIFF with bad preamble: {s}"),
             ExifError::IfdTruncated => f.write_str("TIFF IFD truncated"),
@@ -0,0 +1,135 @@
+use super::lowlevel::{read_u16, read_u32};
+use super::types::*;
+
+const VP8X_ICC: u8 = 0x20;
+const VP8X_ALPHA: u8 = 0x10;
+const VP8X_EXIF: u8 = 0x08;
+const VP8X_XMP: u8 = 0x04;
+
+/// A chunk of a RIFF container
+pub(crate) struct RiffChunk<'a> {
+    pub fourcc: &'a [u8],
+    pub data: &'a [u8],
+}
+
+pub(crate) fn is_webp(contents: &[u8]) -> bool {
+    contents.len() >= 12 && &contents[..4] == b"RIFF" && &contents[8..12] == b"WEBP"
+}
+
+/// Iterates over chunks of a WebP file, up to the first malformed chunk
+pub(crate) fn riff_chunks(contents: &[u8]) -> impl Iterator<Item = RiffChunk<'_>> {
+    let mut rest = contents.get(12..).unwrap_or_default();
+    std::iter::from_fn(move || {
+        let fourcc = rest.get(..4)?;
+        let size = read_u32(true, rest.get(4..)?)? as usize;
+        let data = rest.get(8..8usize.checked_add(size)?)?;
+        // chunks are padded to even size
+        rest = rest.get(8 + size + (size & 1)..).unwrap_or_default();
+        Some(RiffChunk { fourcc, data })
+    })
+}
+
+/// Finds the TIFF in the `EXIF` chunk
+pub(crate) fn find_exif_in_webp(contents: &[u8]) -> Result<&[u8], ExifError> {
+    riff_chunks(contents)
+        .find(|chunk| chunk.fourcc == b"EXIF")
+        // some encoders copy the JPEG APP1 header too
+        .map(|chunk| chunk.data.strip_prefix(EXIF_HEADER).unwrap_or(chunk.data))
+        .ok_or_else(|| ExifError::ImageWithoutExif("WebP has no EXIF chunk".into()))
+}
+
+pub(crate) fn find_xmp_in_webp(contents: &[u8]) -> Option<Vec<u8>> {
+    riff_chunks(contents).find(|chunk| chunk.fourcc == b"XMP ").map(|chunk| chunk.data.to_vec())
+}
+
+pub(crate) fn find_icc_in_webp(contents: &[u8]) -> Option<Vec<u8>> {
+    riff_chunks(contents).find(|chunk| chunk.fourcc == b"ICCP").map(|chunk| chunk.data.to_vec())
+}
+
+/// Returns a copy of the WebP file with the `EXIF` chunk replaced by the serialized `exif`.
+///
+/// Files in the simple format (with only a `VP8 ` or `VP8L` chunk) are converted to
+/// the extended format, because only the extended format can have metadata.
+/// Offsets inside the `MakerNote` are updated to its new position, see `MakernoteOffsets::Fix`.
+pub fn write_exif_to_webp(original: &[u8], exif: &ExifData) -> Result<Vec<u8>, ExifError> {
+    if !is_webp(original) {
+        return Err(ExifError::FileTypeUnknown);
+    }
+    let serialized = exif.serialize_with(MakernoteOffsets::Fix)?;
+    let tiff = serialized.strip_prefix(EXIF_HEADER).unwrap_or(&serialized[..]);
+
+    let chunks: Vec<_> = riff_chunks(original).collect();
+    let mut vp8x = match chunks.iter().find(|c| c.fourcc == b"VP8X") {
+        Some(c) if c.data.len() >= 10 => c.data.to_vec(),
+        Some(_) => return Err(ExifError::CannotWrite("WebP VP8X chunk truncated".into())),
+        None => simple_format_vp8x(&chunks)?,
+    };
+    vp8x[0] |= VP8X_EXIF;
+    if chunks.iter().any(|c| c.fourcc == b"ICCP") {
+        vp8x[0] |= VP8X_ICC;
+    }
+    if chunks.iter().any(|c| c.fourcc == b"XMP ") {
+        vp8x[0] |= VP8X_XMP;
+    }
+
+    let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+    write_chunk(&mut webp, b"VP8X", &vp8x);
+    let chunks: Vec<_> = chunks.into_iter().filter(|c| c.fourcc != b"VP8X" && c.fourcc != b"EXIF").collect();
+    // EXIF goes after the image data, even if unknown chunks come before it
+    let exif_at = chunks.iter().rposition(|c| matches!(c.fourcc, b"VP8 " | b"VP8L" | b"ANMF")).map_or(chunks.len(), |i| i + 1);
+    for (i, chunk) in chunks.iter().enumerate() {
+        if i == exif_at {
+            write_chunk(&mut webp, b"EXIF", tiff);
+        }
+        write_chunk(&mut webp, chunk.fourcc, chunk.data);
+    }
+    if exif_at == chunks.len() {
+        write_chunk(&mut webp, b"EXIF", tiff);
+    }
+
+    let riff_size = u32::try_from(webp.len() - 8).map_err(|_| ExifError::CannotWrite("WebP too large".into()))?;
+    webp[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(webp)
+}
+
+fn write_chunk(webp: &mut Vec<u8>, fourcc: &[u8], data: &[u8]) {
+    webp.extend_from_slice(fourcc);
+    webp.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    webp.extend_from_slice(data);
+    if data.len() & 1 != 0 {
+        webp.push(0);
+    }
+}
+
+/// Creates the `VP8X` chunk for a file in the simple format, with the canvas size
+/// read from the lossy or lossless bitstream header
+fn simple_format_vp8x(chunks: &[RiffChunk<'_>]) -> Result<Vec<u8>, ExifError> {
+    let bitstream = chunks.iter().find(|c| c.fourcc == b"VP8 " || c.fourcc == b"VP8L")
+        .ok_or_else(|| ExifError::CannotWrite("WebP has no image data".into()))?;
+    let truncated = || ExifError::CannotWrite("WebP bitstream header truncated".into());
+
+    let (width, height, alpha) = if bitstream.fourcc == b"VP8 " {
+        // frame tag, start code, 14-bit width and height with 2-bit scale
+        if bitstream.data.get(3..6) != Some(&[0x9d, 0x01, 0x2a]) {
+            return Err(ExifError::CannotWrite("WebP VP8 start code not found".into()));
+        }
+        let width = read_u16(true, bitstream.data.get(6..).ok_or_else(truncated)?).ok_or_else(truncated)? & 0x3fff;
+        let height = read_u16(true, bitstream.data.get(8..).ok_or_else(truncated)?).ok_or_else(truncated)? & 0x3fff;
+        (u32::from(width), u32::from(height), false)
+    } else {
+        // signature, 14-bit width - 1, 14-bit height - 1, alpha bit
+        if bitstream.data.first() != Some(&0x2f) {
+            return Err(ExifError::CannotWrite("WebP VP8L signature not found".into()));
+        }
+        let bits = read_u32(true, bitstream.data.get(1..).ok_or_else(truncated)?).ok_or_else(truncated)?;
+        ((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1, bits & (1 << 28) != 0)
+    };
+    if width == 0 || height == 0 {
+        return Err(ExifError::CannotWrite("WebP has invalid dimensions".into()));
+    }
+
+    let mut vp8x = vec![if alpha { VP8X_ALPHA } else { 0 }, 0, 0, 0];
+    vp8x.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+    Ok(vp8x)
+}
@@ -0,0 +1,657 @@
+use super::image::JpegSegments;
+use super::lowlevel::read_u32;
+use super::exif::tag_to_exif;
+use super::rational::*;
+use super::types::*;
+use std::fmt::{self, Display};
+
+const XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const EXTENDED_XMP_SIGNATURE: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+
+/// A piece of the ExtendedXMP packet, from one APP1 segment
+struct ExtendedXmpChunk<'a> {
+    guid: &'a [u8],
+    full_length: u32,
+    offset: u32,
+    data: &'a [u8],
+}
+
+/// Finds the main XMP packet and the ExtendedXMP packet in the APP1 segments of a JPEG.
+///
+/// ExtendedXMP is split into chunks that are tagged with the MD5 GUID of the whole packet.
+/// Only chunks with the GUID given in `xmpNote:HasExtendedXMP` of the main packet are used.
+pub(crate) fn find_xmp_in_jpeg(contents: &[u8], warnings: &mut Vec<String>) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
+    let mut xmp = None;
+    let mut chunks = Vec::new();
+
+    // Errors in the JPEG structure are reported when looking for the EXIF segment
+    for segment in JpegSegments::new(contents).map_while(Result::ok) {
+        if segment.marker != 0xe1 {
+            continue;
+        }
+        if let Some(packet) = segment.data.strip_prefix(XMP_SIGNATURE) {
+            if xmp.is_none() {
+                xmp = Some(packet);
+            }
+        } else if let Some(chunk) = segment.data.strip_prefix(EXTENDED_XMP_SIGNATURE) {
+            if chunk.len() < 40 {
+                warnings.push("ExtendedXMP chunk truncated".into());
+                continue;
+            }
+            chunks.push(ExtendedXmpChunk {
+                guid: &chunk[..32],
+                full_length: read_u32(false, &chunk[32..36]).unwrap(),
+                offset: read_u32(false, &chunk[36..40]).unwrap(),
+                data: &chunk[40..],
+            });
+        }
+    }
+
+    let extended_xmp = xmp.and_then(extended_xmp_guid)
+        .and_then(|guid| assemble_extended_xmp(guid, &mut chunks, warnings));
+    (xmp.map(<[u8]>::to_vec), extended_xmp)
+}
+
+/// The GUID of the ExtendedXMP packet is a 32-digit hex string (in an attribute or an element)
+fn extended_xmp_guid(xmp: &[u8]) -> Option<&[u8]> {
+    let prop = b"HasExtendedXMP";
+    let pos = xmp.windows(prop.len()).position(|w| w == prop)? + prop.len();
+    let start = pos + xmp.get(pos..)?.iter().position(|&c| c == b'"' || c == b'\'' || c == b'>')? + 1;
+    let guid = xmp.get(start..start + 32)?;
+    if guid.iter().all(u8::is_ascii_hexdigit) {
+        Some(guid)
+    } else {
+        None
+    }
+}
+
+fn assemble_extended_xmp(guid: &[u8], chunks: &mut [ExtendedXmpChunk<'_>], warnings: &mut Vec<String>) -> Option<Vec<u8>> {
+    chunks.sort_by_key(|c| c.offset);
+
+    let mut full_length = None;
+    let mut packet = Vec::new();
+    for chunk in chunks.iter().filter(|c| c.guid == guid) {
+        if *full_length.get_or_insert(chunk.full_length) != chunk.full_length
+            || chunk.offset as usize != packet.len() {
+            warnings.push("ExtendedXMP chunks are inconsistent".into());
+            return None;
+        }
+        packet.extend_from_slice(chunk.data);
+    }
+
+    match full_length {
+        Some(len) if len as usize == packet.len() => Some(packet),
+        Some(_) => {
+            warnings.push("ExtendedXMP is incomplete".into());
+            None
+        },
+        None => {
+            warnings.push("ExtendedXMP not found".into());
+            None
+        },
+    }
+}
+
+/// In TIFF files XMP is stored in a tag of IFD0
+pub(crate) fn find_xmp_in_entries(entries: &[ExifEntry]) -> Option<Vec<u8>> {
+    entries.iter()
+        .find(|e| e.kind == IfdKind::Ifd0 && e.tag == ExifTag::XMP)
+        .map(|e| e.ifd.data.clone())
+}
+
+const RDF_NS: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+/// Namespaces that are reported with their conventional prefix,
+/// regardless of the prefix declared in the packet
+const KNOWN_NAMESPACES: &[(&str, &str)] = &[
+    ("http://ns.adobe.com/exif/1.0/", "exif"),
+    ("http://cipa.jp/exif/1.0/", "exifEX"),
+    ("http://ns.adobe.com/tiff/1.0/", "tiff"),
+    ("http://ns.adobe.com/xap/1.0/", "xmp"),
+    ("http://ns.adobe.com/xap/1.0/mm/", "xmpMM"),
+    ("http://ns.adobe.com/xap/1.0/rights/", "xmpRights"),
+    ("http://ns.adobe.com/xmp/note/", "xmpNote"),
+    ("http://purl.org/dc/elements/1.1/", "dc"),
+    ("http://ns.adobe.com/photoshop/1.0/", "photoshop"),
+    ("http://ns.adobe.com/exif/1.0/aux/", "aux"),
+    ("http://ns.adobe.com/camera-raw-settings/1.0/", "crs"),
+    ("http://iptc.org/std/Iptc4xmpCore/1.0/xmlns/", "Iptc4xmpCore"),
+];
+
+/// Value of an XMP property
+#[derive(Clone, Debug, PartialEq)]
+pub enum XmpValue {
+    /// Text, number, date, or URI
+    Simple(String),
+    /// Items of `rdf:Seq`, `rdf:Bag` or `rdf:Alt`. Language alternatives are in the original order,
+    /// which usually puts `x-default` first.
+    Array(Vec<String>),
+}
+
+impl XmpValue {
+    /// The value, or the first item of an array
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Simple(s) => Some(s),
+            Self::Array(items) => items.first().map(String::as_str),
+        }
+    }
+}
+
+impl Display for XmpValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Simple(s) => f.write_str(s),
+            Self::Array(items) => f.write_str(&items.join(", ")),
+        }
+    }
+}
+
+/// A top-level property of the XMP packet
+#[derive(Clone, Debug, PartialEq)]
+pub struct XmpProperty {
+    /// Namespace prefix, e.g. "dc". Well-known namespaces always use their conventional prefix.
+    pub prefix: String,
+    /// Name of the property without the prefix, e.g. "title"
+    pub name: String,
+    /// EXIF tag with the same meaning, for properties of the `exif:`, `exifEX:`, `tiff:` and `xmp:` namespaces
+    pub tag: Option<ExifTag>,
+    pub value: XmpValue,
+}
+
+/// Parsed XMP properties.
+///
+/// This is a lightweight parser that only reads simple and array properties
+/// of `rdf:Description` elements. Structures (like `exif:Flash`) and qualifiers are skipped.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Xmp {
+    pub properties: Vec<XmpProperty>,
+}
+
+impl Xmp {
+    /// Parse an XMP packet. Malformed XML is parsed as far as possible.
+    #[must_use]
+    pub fn parse(packet: &[u8]) -> Self {
+        let mut xmp = Self::default();
+        xmp.parse_packet(packet);
+        xmp
+    }
+
+    /// Property by its prefixed name, e.g. "dc:creator"
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&XmpValue> {
+        let (prefix, name) = key.split_once(':')?;
+        self.properties.iter()
+            .find(|p| p.prefix == prefix && p.name == name)
+            .map(|p| &p.value)
+    }
+
+    /// Property that corresponds to the EXIF tag
+    #[must_use]
+    pub fn get_tag(&self, tag: ExifTag) -> Option<&XmpValue> {
+        self.properties.iter()
+            .find(|p| p.tag == Some(tag))
+            .map(|p| &p.value)
+    }
+
+    /// Adds properties from another packet, e.g. ExtendedXMP
+    pub(crate) fn parse_packet(&mut self, packet: &[u8]) {
+        let text = String::from_utf8_lossy(packet);
+        let events = xml_events(&text);
+        let mut namespaces = Vec::new();
+        let mut i = 0;
+        while i < events.len() {
+            if let XmlEvent::Start { name, attrs, empty } = &events[i] {
+                declare_namespaces(attrs, &mut namespaces);
+                if resolve(name, &namespaces) == Some((RDF_NS, "Description")) {
+                    self.add_attribute_properties(attrs, &namespaces);
+                    if !*empty {
+                        i = self.parse_description(&events, i + 1, &mut namespaces);
+                        continue;
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
+
+    fn add_attribute_properties(&mut self, attrs: &[(String, String)], namespaces: &[(String, String)]) {
+        for (name, value) in attrs {
+            if let Some((uri, local)) = resolve(name, namespaces) {
+                if uri != RDF_NS {
+                    self.push(uri, name, local, XmpValue::Simple(value.clone()));
+                }
+            }
+        }
+    }
+
+    /// Reads children of `rdf:Description` and returns the index after its end
+    fn parse_description(&mut self, events: &[XmlEvent<'_>], mut i: usize, namespaces: &mut Vec<(String, String)>) -> usize {
+        while i < events.len() {
+            match &events[i] {
+                XmlEvent::Start { name, attrs, empty } => {
+                    declare_namespaces(attrs, namespaces);
+                    let value = if *empty {
+                        i += 1;
+                        attr(attrs, "rdf:resource").map(|r| XmpValue::Simple(r.to_string()))
+                    } else {
+                        let (value, next) = parse_property_value(events, i + 1, attrs);
+                        i = next;
+                        value
+                    };
+                    if let (Some(value), Some((uri, local))) = (value, resolve(name, namespaces)) {
+                        self.push(uri, name, local, value);
+                    }
+                },
+                XmlEvent::End => return i + 1,
+                XmlEvent::Text(_) => i += 1,
+            }
+        }
+        i
+    }
+
+    fn push(&mut self, uri: &str, name: &str, local: &str, value: XmpValue) {
+        let prefix = KNOWN_NAMESPACES.iter()
+            .find(|&&(known, _)| known == uri)
+            .map_or_else(|| name.split_once(':').map_or("", |(p, _)| p), |&(_, prefix)| prefix);
+        self.properties.push(XmpProperty {
+            tag: xmp_to_exif_tag(prefix, local),
+            prefix: prefix.into(),
+            name: local.into(),
+            value,
+        });
+    }
+}
+
+/// Reads the value of a property element, and returns the index after its end.
+/// Structures are skipped and have no value.
+fn parse_property_value(events: &[XmlEvent<'_>], mut i: usize, attrs: &[(String, String)]) -> (Option<XmpValue>, usize) {
+    if attr(attrs, "rdf:parseType") == Some("Resource") {
+        return (None, skip_element(events, i));
+    }
+
+    let mut text = String::new();
+    let mut value = None;
+    while i < events.len() {
+        match &events[i] {
+            XmlEvent::Text(t) => {
+                text.push_str(t);
+                i += 1;
+            },
+            XmlEvent::Start { name, empty, .. } => {
+                if !matches!(*name, "rdf:Seq" | "rdf:Bag" | "rdf:Alt") {
+                    // a structure
+                    return (None, skip_element(events, i));
+                }
+                let (items, next) = if *empty { (Vec::new(), i + 1) } else { parse_array_items(events, i + 1) };
+                value = Some(XmpValue::Array(items));
+                i = next;
+            },
+            XmlEvent::End => {
+                i += 1;
+                break;
+            },
+        }
+    }
+    (Some(value.unwrap_or_else(|| XmpValue::Simple(text.trim().to_string()))), i)
+}
+
+/// Reads text of `rdf:li` elements, and returns the index after the end of the array
+fn parse_array_items(events: &[XmlEvent<'_>], mut i: usize) -> (Vec<String>, usize) {
+    let mut items = Vec::new();
+    while i < events.len() {
+        match &events[i] {
+            XmlEvent::Start { empty: false, .. } => {
+                let end = skip_element(events, i + 1);
+                items.push(events[i + 1..end].iter().filter_map(|e| match e {
+                    XmlEvent::Text(t) => Some(&t[..]),
+                    _ => None,
+                }).collect::<String>().trim().to_string());
+                i = end;
+            },
+            XmlEvent::Start { empty: true, .. } => {
+                items.push(String::new());
+                i += 1;
+            },
+            XmlEvent::End => return (items, i + 1),
+            XmlEvent::Text(_) => i += 1,
+        }
+    }
+    (items, i)
+}
+
+/// Index after the end of the element whose contents start at `i`
+fn skip_element(events: &[XmlEvent<'_>], mut i: usize) -> usize {
+    let mut depth = 1;
+    while i < events.len() && depth > 0 {
+        match events[i] {
+            XmlEvent::Start { empty: false, .. } => depth += 1,
+            XmlEvent::End => depth -= 1,
+            _ => {},
+        }
+        i += 1;
+    }
+    i
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+}
+
+/// XML namespaces are scoped to elements, but XMP packets in practice
+/// never reuse a prefix for different namespaces, so a flat list is enough.
+fn declare_namespaces(attrs: &[(String, String)], namespaces: &mut Vec<(String, String)>) {
+    for (name, value) in attrs {
+        if let Some(prefix) = name.strip_prefix("xmlns:") {
+            namespaces.push((prefix.into(), value.clone()));
+        }
+    }
+}
+
+/// Namespace URI and local name of a prefixed XML name
+fn resolve<'a>(name: &'a str, namespaces: &'a [(String, String)]) -> Option<(&'a str, &'a str)> {
+    let (prefix, local) = name.split_once(':')?;
+    namespaces.iter().rev()
+        .find(|(p, _)| p == prefix)
+        .map(|(_, uri)| (uri.as_str(), local))
+}
+
+/// Maps properties of the EXIF-related namespaces onto EXIF tags with the same meaning
+fn xmp_to_exif_tag(prefix: &str, name: &str) -> Option<ExifTag> {
+    Some(match (prefix, name) {
+        ("tiff", "ImageDescription") => ExifTag::ImageDescription,
+        ("tiff", "Make") => ExifTag::Make,
+        ("tiff", "Model") => ExifTag::Model,
+        ("tiff", "Orientation") => ExifTag::Orientation,
+        ("tiff", "XResolution") => ExifTag::XResolution,
+        ("tiff", "YResolution") => ExifTag::YResolution,
+        ("tiff", "ResolutionUnit") => ExifTag::ResolutionUnit,
+        ("tiff", "Software") => ExifTag::Software,
+        ("tiff", "DateTime") => ExifTag::DateTime,
+        ("tiff", "WhitePoint") => ExifTag::WhitePoint,
+        ("tiff", "PrimaryChromaticities") => ExifTag::PrimaryChromaticities,
+        ("tiff", "YCbCrCoefficients") => ExifTag::YCbCrCoefficients,
+        ("tiff", "ReferenceBlackWhite") => ExifTag::ReferenceBlackWhite,
+        ("tiff", "Copyright") => ExifTag::Copyright,
+
+        ("xmp", "CreatorTool") => ExifTag::Software,
+        ("xmp", "ModifyDate") => ExifTag::DateTime,
+        ("xmp", "CreateDate") => ExifTag::DateTimeDigitized,
+
+        ("exif", "ExifVersion") => ExifTag::ExifVersion,
+        ("exif", "FlashpixVersion") => ExifTag::FlashPixVersion,
+        ("exif", "ColorSpace") => ExifTag::ColorSpace,
+        ("exif", "UserComment") => ExifTag::UserComment,
+        ("exif", "RelatedSoundFile") => ExifTag::RelatedSoundFile,
+        ("exif", "DateTimeOriginal") => ExifTag::DateTimeOriginal,
+        ("exif", "DateTimeDigitized") => ExifTag::DateTimeDigitized,
+        ("exif", "ExposureTime") => ExifTag::ExposureTime,
+        ("exif", "FNumber") => ExifTag::FNumber,
+        ("exif", "ExposureProgram") => ExifTag::ExposureProgram,
+        ("exif", "SpectralSensitivity") => ExifTag::SpectralSensitivity,
+        ("exif", "ISOSpeedRatings") => ExifTag::ISOSpeedRatings,
+        ("exif", "ShutterSpeedValue") => ExifTag::ShutterSpeedValue,
+        ("exif", "ApertureValue") => ExifTag::ApertureValue,
+        ("exif", "BrightnessValue") => ExifTag::BrightnessValue,
+        ("exif", "ExposureBiasValue") => ExifTag::ExposureBiasValue,
+        ("exif", "MaxApertureValue") => ExifTag::MaxApertureValue,
+        ("exif", "SubjectDistance") => ExifTag::SubjectDistance,
+        ("exif", "MeteringMode") => ExifTag::MeteringMode,
+        ("exif", "LightSource") => ExifTag::LightSource,
+        ("exif", "FocalLength") => ExifTag::FocalLength,
+        ("exif", "SubjectArea") => ExifTag::SubjectArea,
+        ("exif", "FlashEnergy") => ExifTag::FlashEnergy,
+        ("exif", "FocalPlaneXResolution") => ExifTag::FocalPlaneXResolution,
+        ("exif", "FocalPlaneYResolution") => ExifTag::FocalPlaneYResolution,
+        ("exif", "FocalPlaneResolutionUnit") => ExifTag::FocalPlaneResolutionUnit,
+        ("exif", "SubjectLocation") => ExifTag::SubjectLocation,
+        ("exif", "ExposureIndex") => ExifTag::ExposureIndex,
+        ("exif", "SensingMethod") => ExifTag::SensingMethod,
+        ("exif", "FileSource") => ExifTag::FileSource,
+        ("exif", "SceneType") => ExifTag::SceneType,
+        ("exif", "CustomRendered") => ExifTag::CustomRendered,
+        ("exif", "ExposureMode") => ExifTag::ExposureMode,
+        ("exif", "WhiteBalance") => ExifTag::WhiteBalanceMode,
+        ("exif", "DigitalZoomRatio") => ExifTag::DigitalZoomRatio,
+        ("exif", "FocalLengthIn35mmFilm") => ExifTag::FocalLengthIn35mmFilm,
+        ("exif", "SceneCaptureType") => ExifTag::SceneCaptureType,
+        ("exif", "GainControl") => ExifTag::GainControl,
+        ("exif", "Contrast") => ExifTag::Contrast,
+        ("exif", "Saturation") => ExifTag::Saturation,
+        ("exif", "Sharpness") => ExifTag::Sharpness,
+        ("exif", "SubjectDistanceRange") => ExifTag::SubjectDistanceRange,
+        ("exif", "ImageUniqueID") => ExifTag::ImageUniqueID,
+        ("exif", "GPSVersionID") => ExifTag::GPSVersionID,
+        ("exif", "GPSLatitude") => ExifTag::GPSLatitude,
+        ("exif", "GPSLongitude") => ExifTag::GPSLongitude,
+        ("exif", "GPSAltitudeRef") => ExifTag::GPSAltitudeRef,
+        ("exif", "GPSAltitude") => ExifTag::GPSAltitude,
+        ("exif", "GPSTimeStamp") => ExifTag::GPSTimeStamp,
+        ("exif", "GPSSatellites") => ExifTag::GPSSatellites,
+        ("exif", "GPSStatus") => ExifTag::GPSStatus,
+        ("exif", "GPSMeasureMode") => ExifTag::GPSMeasureMode,
+        ("exif", "GPSDOP") => ExifTag::GPSDOP,
+        ("exif", "GPSSpeedRef") => ExifTag::GPSSpeedRef,
+        ("exif", "GPSSpeed") => ExifTag::GPSSpeed,
+        ("exif", "GPSTrackRef") => ExifTag::GPSTrackRef,
+        ("exif", "GPSTrack") => ExifTag::GPSTrack,
+        ("exif", "GPSImgDirectionRef") => ExifTag::GPSImgDirectionRef,
+        ("exif", "GPSImgDirection") => ExifTag::GPSImgDirection,
+        ("exif", "GPSMapDatum") => ExifTag::GPSMapDatum,
+        ("exif", "GPSDestLatitude") => ExifTag::GPSDestLatitude,
+        ("exif", "GPSDestLongitude") => ExifTag::GPSDestLongitude,
+        ("exif", "GPSDestBearingRef") => ExifTag::GPSDestBearingRef,
+        ("exif", "GPSDestBearing") => ExifTag::GPSDestBearing,
+        ("exif", "GPSDestDistanceRef") => ExifTag::GPSDestDistanceRef,
+        ("exif", "GPSDestDistance") => ExifTag::GPSDestDistance,
+        ("exif", "GPSProcessingMethod") => ExifTag::GPSProcessingMethod,
+        ("exif", "GPSAreaInformation") => ExifTag::GPSAreaInformation,
+        ("exif", "GPSDifferential") => ExifTag::GPSDifferential,
+
+        ("exifEX", "SensitivityType") => ExifTag::SensitivityType,
+        ("exifEX", "LensSpecification") => ExifTag::LensSpecification,
+        ("exifEX", "LensMake") => ExifTag::LensMake,
+        ("exifEX", "LensModel") => ExifTag::LensModel,
+        ("exifEX", "LensSerialNumber") => ExifTag::LensSerialNumber,
+        ("exifEX", "CameraOwnerName") => ExifTag::CameraOwnerName,
+        ("exifEX", "BodySerialNumber") => ExifTag::BodySerialNumber,
+        ("exifEX", "Gamma") => ExifTag::Gamma,
+        _ => return None,
+    })
+}
+
+/// Converts an XMP value to the format of the EXIF tag
+pub(crate) fn xmp_tag_value(xmp: &Xmp, tag: ExifTag) -> Option<TagValue> {
+    // XMP has the reference in the same property as the coordinate, e.g. "37,46.5N"
+    let coordinate = match tag {
+        ExifTag::GPSLatitudeRef => Some(ExifTag::GPSLatitude),
+        ExifTag::GPSLongitudeRef => Some(ExifTag::GPSLongitude),
+        ExifTag::GPSDestLatitudeRef => Some(ExifTag::GPSDestLatitude),
+        ExifTag::GPSDestLongitudeRef => Some(ExifTag::GPSDestLongitude),
+        _ => None,
+    };
+    if let Some(coordinate) = coordinate {
+        let value = xmp.get_tag(coordinate)?.as_str()?.trim();
+        return value.get(value.len().saturating_sub(1)..).map(|r| TagValue::Ascii(r.into()));
+    }
+
+    let value = xmp.get_tag(tag)?;
+    let as_ascii = || TagValue::Ascii(value.as_str().unwrap_or_default().to_string());
+    if matches!(tag, ExifTag::GPSLatitude | ExifTag::GPSLongitude | ExifTag::GPSDestLatitude | ExifTag::GPSDestLongitude) {
+        return Some(gps_coordinate(value.as_str()?).unwrap_or_else(as_ascii));
+    }
+
+    let items: Vec<&str> = match value {
+        XmpValue::Simple(s) if tag == ExifTag::GPSVersionID => s.split('.').collect(),
+        XmpValue::Simple(s) => s.split(|c: char| c == ',' || c.is_whitespace()).filter(|s| !s.is_empty()).collect(),
+        XmpValue::Array(items) => items.iter().map(|s| s.trim()).collect(),
+    };
+    let (_, _, format, ..) = tag_to_exif(tag.code());
+    let converted = match format {
+        IfdFormat::U8 => items.iter().map(|s| s.parse().ok()).collect::<Option<_>>().map(TagValue::U8),
+        IfdFormat::U16 => items.iter().map(|s| s.parse().ok()).collect::<Option<_>>().map(TagValue::U16),
+        IfdFormat::U32 => items.iter().map(|s| s.parse().ok()).collect::<Option<_>>().map(TagValue::U32),
+        IfdFormat::I16 => items.iter().map(|s| s.parse().ok()).collect::<Option<_>>().map(TagValue::I16),
+        IfdFormat::I32 => items.iter().map(|s| s.parse().ok()).collect::<Option<_>>().map(TagValue::I32),
+        IfdFormat::URational => items.iter().map(|s| parse_urational(s)).collect::<Option<_>>().map(TagValue::URational),
+        IfdFormat::IRational => items.iter().map(|s| parse_irational(s)).collect::<Option<_>>().map(TagValue::IRational),
+        IfdFormat::Undefined if tag != ExifTag::UserComment => {
+            value.as_str().map(|s| TagValue::Undefined(s.as_bytes().to_vec(), false))
+        },
+        IfdFormat::Ascii if matches!(tag, ExifTag::DateTime | ExifTag::DateTimeOriginal | ExifTag::DateTimeDigitized) => {
+            value.as_str().map(|s| TagValue::Ascii(exif_date(s)))
+        },
+        _ => None,
+    };
+    Some(converted.unwrap_or_else(as_ascii))
+}
+
+/// "1/200", "8" or "2.8"
+fn parse_urational(s: &str) -> Option<URational> {
+    let r = parse_irational(s)?;
+    Some(URational {
+        numerator: r.numerator.try_into().ok()?,
+        denominator: r.denominator.try_into().ok()?,
+    })
+}
+
+fn parse_irational(s: &str) -> Option<IRational> {
+    if let Some((numerator, denominator)) = s.split_once('/') {
+        return Some(IRational { numerator: numerator.trim().parse().ok()?, denominator: denominator.trim().parse().ok()? });
+    }
+    let (int, frac) = s.trim().split_once('.').unwrap_or((s.trim(), ""));
+    let frac = &frac[..frac.len().min(6)];
+    let denominator = 10i32.pow(frac.len() as u32);
+    let numerator: i32 = format!("{int}{frac}").parse().ok()?;
+    Some(IRational { numerator, denominator })
+}
+
+/// XMP uses "DDD,MM,SSk" or "DDD,MM.mmk", where k is the reference direction
+fn gps_coordinate(s: &str) -> Option<TagValue> {
+    let s = s.trim();
+    let parts = s.get(..s.len().checked_sub(1)?)?.split(',').map(parse_urational).collect::<Option<Vec<_>>>()?;
+    let zero = URational { numerator: 0, denominator: 1 };
+    match parts[..] {
+        [d, m, s] => Some(TagValue::URational(vec![d, m, s])),
+        [d, m] => Some(TagValue::URational(vec![d, m, zero])),
+        _ => None,
+    }
+}
+
+/// ISO 8601 "2020-01-02T03:04:05+01:00" to EXIF "2020:01:02 03:04:05"
+pub(crate) fn exif_date(s: &str) -> String {
+    let s = s.trim();
+    let (date, time) = s.split_once('T').unwrap_or((s, ""));
+    let time = time.get(..8).unwrap_or(time);
+    let date = date.replace('-', ":");
+    if time.is_empty() { date } else { format!("{date} {time}") }
+}
+
+enum XmlEvent<'a> {
+    Start {
+        name: &'a str,
+        attrs: Vec<(String, String)>,
+        empty: bool,
+    },
+    End,
+    Text(String),
+}
+
+/// Splits XML into tags and text. Comments, processing instructions and DTDs are skipped.
+fn xml_events(xml: &str) -> Vec<XmlEvent<'_>> {
+    let mut events = Vec::new();
+    let mut rest = xml;
+    while !rest.is_empty() {
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            events.push(XmlEvent::Text(cdata[..end].into()));
+            rest = cdata.get(end + 3..).unwrap_or("");
+        } else if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some(tag) = rest.strip_prefix("</") {
+            events.push(XmlEvent::End);
+            rest = tag.find('>').map_or("", |end| &tag[end + 1..]);
+        } else if let Some(tag) = rest.strip_prefix('<') {
+            let name_end = tag.find(|c: char| c.is_whitespace() || c == '>' || c == '/').unwrap_or(tag.len());
+            let name = &tag[..name_end];
+            let mut attrs = Vec::new();
+            let mut tag = &tag[name_end..];
+            loop {
+                tag = tag.trim_start();
+                if let Some(after) = tag.strip_prefix("/>") {
+                    events.push(XmlEvent::Start { name, attrs, empty: true });
+                    rest = after;
+                    break;
+                }
+                if let Some(after) = tag.strip_prefix('>') {
+                    events.push(XmlEvent::Start { name, attrs, empty: false });
+                    rest = after;
+                    break;
+                }
+                let parsed = tag.split_once('=').and_then(|(attr_name, value)| {
+                    let value = value.trim_start();
+                    let quote = value.chars().next().filter(|&q| q == '"' || q == '\'')?;
+                    let (value, after) = value[1..].split_once(quote)?;
+                    Some((attr_name.trim(), value, after))
+                });
+                match parsed {
+                    Some((attr_name, value, after)) => {
+                        attrs.push((attr_name.into(), decode_entities(value)));
+                        tag = after;
+                    },
+                    None => {
+                        // malformed tag ends the document
+                        events.push(XmlEvent::Start { name, attrs, empty: true });
+                        return events;
+                    },
+                }
+            }
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            events.push(XmlEvent::Text(decode_entities(&rest[..end])));
+            rest = &rest[end..];
+        }
+    }
+    events
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                num => {
+                    let code = match num.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => num.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                },
+            };
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            },
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -151,3 +151,1157 @@ fn test_serialize_empty() {
     let tiff_header = [b'M', b'M', 0, 42, 0, 0, 0, 8, 0, 0, 0, 0, 0, 0];
     assert_eq!(exif.serialize().unwrap(), [EXIF_HEADER, &tiff_header].concat());
 }
+
+#[test]
+fn test_parse_jpeg_thumbnail() {
+    let exif = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+
+    assert!(exif.entries.iter().any(|e| e.kind == IfdKind::Ifd1 && e.tag == ExifTag::JPEGInterchangeFormat));
+    let thumbnail = exif.thumbnail().expect("thumbnail");
+    assert_eq!(thumbnail.len(), 1378);
+    assert_eq!(&thumbnail[..2], &[0xff, 0xd8]);
+
+    let exif = rexif::parse_file("./tests/img/profile.jpg").unwrap();
+    assert!(exif.thumbnail().is_none());
+}
+
+#[test]
+fn test_thumbnail_serialization() {
+    let exif = rexif::parse_file("./tests/img/jpg/Nikon_D70.jpg").unwrap();
+    let serialized = exif.serialize().unwrap();
+    let size = (serialized.len() as u16 + 2).to_be_bytes();
+    let reparsed = parse_buffer(&[APP_MARKER, &size, &serialized].concat()).unwrap();
+
+    assert_eq!(exif.thumbnail(), reparsed.thumbnail());
+    assert!(reparsed.thumbnail().is_some());
+
+    // Without the thumbnail data, the pointer to it is not written
+    let mut exif = exif;
+    let entry = exif.entries.iter_mut().find(|e| e.kind == IfdKind::Ifd1 && e.tag == ExifTag::JPEGInterchangeFormat).unwrap();
+    entry.ifd.ext_data.clear();
+    let serialized = exif.serialize().unwrap();
+    let size = (serialized.len() as u16 + 2).to_be_bytes();
+    let reparsed = parse_buffer(&[APP_MARKER, &size, &serialized].concat()).unwrap();
+    assert!(reparsed.entries.iter().any(|e| e.kind == IfdKind::Ifd1));
+    assert!(!reparsed.entries.iter().any(|e| matches!(e.tag, ExifTag::JPEGInterchangeFormat | ExifTag::JPEGInterchangeFormatLength)));
+}
+
+#[test]
+fn test_parse_interoperability_ifd() {
+    let exif = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+
+    let index = exif.entries.iter()
+        .find(|e| e.tag == ExifTag::InteroperabilityIndex)
+        .expect("InteroperabilityIndex");
+    assert_eq!(index.kind, IfdKind::Interoperability);
+    assert_eq!(index.value, TagValue::Ascii("R98".into()));
+
+    // Interop tags must not be confused with the GPS tags that have the same codes
+    assert!(!exif.entries.iter().any(|e| e.kind == IfdKind::Interoperability && e.tag == ExifTag::GPSLatitudeRef));
+
+    let serialized = exif.serialize().unwrap();
+    let size = (serialized.len() as u16 + 2).to_be_bytes();
+    let reparsed = parse_buffer(&[APP_MARKER, &size, &serialized].concat()).unwrap();
+    assert_eq!(
+        reparsed.entries.iter().filter(|e| e.kind == IfdKind::Interoperability).count(),
+        exif.entries.iter().filter(|e| e.kind == IfdKind::Interoperability).count()
+    );
+}
+
+#[test]
+fn test_parse_canon_makernote() {
+    let exif = rexif::parse_file("./tests/img/jpg/Canon_DIGITAL_IXUS_400.jpg").unwrap();
+
+    let owner = exif.entries.iter().find(|e| e.tag == ExifTag::CanonOwnerName).expect("owner name");
+    assert_eq!(owner.namespace, Namespace::Canon);
+    assert_eq!(owner.kind, IfdKind::Makernote);
+    assert_eq!(owner.value, TagValue::Ascii("Jean-Pierre Grignon".into()));
+
+    let settings = exif.entries.iter().find(|e| e.tag == ExifTag::CanonCameraSettings).expect("camera settings");
+    assert!(settings.value_more_readable.starts_with("Focus mode: Single"), "{}", settings.value_more_readable);
+}
+
+#[test]
+fn test_parse_nikon_makernote() {
+    let exif = rexif::parse_file("./tests/img/jpg/gps/DSCN0010.jpg").unwrap();
+
+    let focus = exif.entries.iter().find(|e| e.tag == ExifTag::NikonFocusMode).expect("focus mode");
+    assert_eq!(focus.namespace, Namespace::Nikon);
+    assert_eq!(focus.kind, IfdKind::Makernote);
+    assert_eq!(focus.value_more_readable.trim(), "AF-S");
+}
+
+#[test]
+fn test_parse_fujifilm_olympus_panasonic_makernotes() {
+    let exif = rexif::parse_file("./tests/img/jpg/Fujifilm_FinePix_E500.jpg").unwrap();
+    let focus = exif.entries.iter().find(|e| e.tag == ExifTag::FujifilmFocusMode).expect("fujifilm focus mode");
+    assert_eq!(focus.namespace, Namespace::Fujifilm);
+    assert_eq!(focus.value_more_readable, "Auto");
+
+    let exif = rexif::parse_file("./tests/img/jpg/corrupted.jpg").unwrap();
+    let serial = exif.entries.iter().find(|e| e.tag == ExifTag::OlympusSerialNumber).expect("olympus serial number");
+    assert_eq!(serial.namespace, Namespace::OlympusEquipment);
+    assert_eq!(serial.value_more_readable.trim(), "F70501740");
+    let focus = exif.entries.iter().find(|e| e.tag == ExifTag::OlympusFocusMode).expect("olympus focus mode");
+    assert_eq!(focus.value_more_readable, "Single AF");
+
+    let exif = rexif::parse_file("./tests/img/jpg/Panasonic_DMC-FZ30.jpg").unwrap();
+    let quality = exif.entries.iter().find(|e| e.tag == ExifTag::PanasonicImageQuality).expect("panasonic quality");
+    assert_eq!(quality.namespace, Namespace::Panasonic);
+    assert_eq!(quality.value_more_readable, "High");
+}
+
+/// Little-endian TIFF with a `Make` tag in IFD0 and a `MakerNote` in the Exif IFD
+fn tiff_with_makernote(make: &[u8], makernote: &[u8]) -> Vec<u8> {
+    let make_offset = 8 + 30;
+    let exif_offset = make_offset + make.len() as u32 + 1;
+    let makernote_offset = exif_offset + 18;
+
+    let mut tiff = vec![b'I', b'I', 42, 0, 8, 0, 0, 0, 2, 0];
+    tiff.extend([0x0f, 0x01, 2, 0]);
+    tiff.extend((make.len() as u32 + 1).to_le_bytes());
+    tiff.extend(make_offset.to_le_bytes());
+    tiff.extend([0x69, 0x87, 4, 0, 1, 0, 0, 0]);
+    tiff.extend(exif_offset.to_le_bytes());
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(make);
+    tiff.push(0);
+    tiff.extend([1, 0, 0x7c, 0x92, 7, 0]);
+    tiff.extend((makernote.len() as u32).to_le_bytes());
+    tiff.extend(makernote_offset.to_le_bytes());
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(makernote);
+    tiff
+}
+
+#[test]
+fn test_parse_sony_pentax_makernotes() {
+    // Headerless IFD with focus mode AF-C and lens type 32784
+    let sony = [
+        2, 0,
+        0x42, 0xb0, 3, 0, 1, 0, 0, 0, 2, 0, 0, 0,
+        0x27, 0xb0, 4, 0, 1, 0, 0, 0, 0x10, 0x80, 0, 0,
+        0, 0, 0, 0,
+    ];
+    let exif = parse_buffer(&tiff_with_makernote(b"SONY", &sony)).unwrap();
+    let focus = exif.entries.iter().find(|e| e.tag == ExifTag::SonyFocusMode).expect("sony focus mode");
+    assert_eq!(focus.namespace, Namespace::Sony);
+    assert_eq!(focus.kind, IfdKind::Makernote);
+    assert_eq!(focus.value_more_readable, "AF-C");
+    let lens = exif.entries.iter().find(|e| e.tag == ExifTag::SonyLensType).expect("sony lens type");
+    assert_eq!(lens.value, TagValue::U32(vec![32784]));
+
+    // "AOC\0" header with byte order, and focus mode AF-S
+    let pentax = [
+        b'A', b'O', b'C', 0, b'I', b'I',
+        1, 0,
+        0x0d, 0, 3, 0, 1, 0, 0, 0, 16, 0, 0, 0,
+        0, 0, 0, 0,
+    ];
+    let exif = parse_buffer(&tiff_with_makernote(b"PENTAX Corporation", &pentax)).unwrap();
+    let focus = exif.entries.iter().find(|e| e.tag == ExifTag::PentaxFocusMode).expect("pentax focus mode");
+    assert_eq!(focus.namespace, Namespace::Pentax);
+    assert_eq!(focus.value_more_readable, "AF-S");
+}
+
+/// Offsets of the values that don't fit in the entries of the little-endian makernote IFD at `start`
+fn makernote_value_offsets(tiff: &[u8], start: usize) -> Vec<usize> {
+    let count = u16::from_le_bytes([tiff[start], tiff[start + 1]]) as usize;
+    tiff[start + 2..start + 2 + count * 12].chunks(12).filter_map(|entry| {
+        let format = u16::from_le_bytes([entry[2], entry[3]]);
+        let count = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]) as usize;
+        let size = match format { 3 | 8 => 2, 4 | 9 | 11 => 4, 5 | 10 | 12 => 8, _ => 1 };
+        (count * size > 4).then(|| u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize)
+    }).collect()
+}
+
+#[test]
+fn test_serialize_fixes_makernote_offsets() {
+    let contents = std::fs::read("./tests/img/jpg/Canon_DIGITAL_IXUS_400.jpg").unwrap();
+    let exif = parse_buffer(&contents).unwrap();
+    let makernote_start = |exif: &ExifData| exif.entries.iter().find(|e| e.tag == ExifTag::MakerNote).unwrap().ifd.try_data_as_offset().unwrap();
+    let tiff_start = contents.windows(EXIF_HEADER.len()).position(|w| w == EXIF_HEADER).unwrap() + EXIF_HEADER.len();
+    let old_start = makernote_start(&exif);
+    let old_offsets = makernote_value_offsets(&contents[tiff_start..], old_start);
+    assert!(!old_offsets.is_empty());
+
+    // The raw offsets are checked, because the parser corrects makernotes that have been moved
+    let serialize = |mode| {
+        let serialized = exif.serialize_with(mode).unwrap();
+        let size = (serialized.len() as u16 + 2).to_be_bytes();
+        let start = makernote_start(&parse_buffer(&[APP_MARKER, &size, &serialized].concat()).unwrap());
+        let offsets = makernote_value_offsets(&serialized[EXIF_HEADER.len()..], start);
+        (start, offsets)
+    };
+
+    // Canon makernote offsets are relative to the TIFF header, so they must point into the new makernote position
+    let (start, fixed) = serialize(MakernoteOffsets::Fix);
+    assert_ne!(start, old_start);
+    let shifted: Vec<_> = old_offsets.iter().map(|&o| o + start - old_start).collect();
+    assert_eq!(fixed, shifted);
+    let count = u16::from_le_bytes([contents[tiff_start + old_start], contents[tiff_start + old_start + 1]]) as usize;
+    // Values start right after the IFD and its next IFD offset
+    assert_eq!(fixed.iter().min(), Some(&(start + 2 + count * 12 + 4)));
+
+    let (unchanged_start, unchanged) = serialize(MakernoteOffsets::Unchanged);
+    assert_eq!(unchanged_start, start);
+    assert_eq!(unchanged, old_offsets);
+    assert_ne!(unchanged, fixed);
+}
+
+#[test]
+fn test_detect_jpeg_starting_with_other_segments() {
+    let original = std::fs::read("./tests/img/profile.jpg").unwrap();
+    let expected = parse_buffer(&original).unwrap();
+
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend([0xff, 0xfe, 0, 7, b'h', b'e', b'l', b'l', b'o']);
+    jpeg.extend([0xff, 0xee, 0, 4, 0, 0]);
+    jpeg.extend(&original[2..]);
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.mime, "image/jpeg");
+    assert_eq!(exif.entries, expected.entries);
+
+    // SOI, COM and SOS without any EXIF
+    let jpeg = [0xff, 0xd8, 0xff, 0xfe, 0, 7, b'h', b'e', b'l', b'l', b'o', 0xff, 0xda, 0, 2];
+    assert!(matches!(parse_buffer(&jpeg), Err(ExifError::JpegWithoutExif(_))));
+}
+
+#[test]
+fn test_skip_non_exif_app1_segments() {
+    let original = std::fs::read("./tests/img/profile.jpg").unwrap();
+    let expected = parse_buffer(&original).unwrap();
+
+    let xmp = b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta xmlns:x='adobe:ns:meta/'/>";
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend([0xff, 0xe1]);
+    jpeg.extend((xmp.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(xmp);
+    // fill bytes, RST0 and TEM markers
+    jpeg.extend([0xff, 0xff, 0xff, 0xd0, 0xff, 0x01]);
+    jpeg.extend(&original[2..]);
+
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.mime, "image/jpeg");
+    assert_eq!(exif.entries, expected.entries);
+}
+
+fn jpeg_app1(payload: &[&[u8]]) -> Vec<u8> {
+    let payload = payload.concat();
+    let mut segment = vec![0xff, 0xe1];
+    segment.extend((payload.len() as u16 + 2).to_be_bytes());
+    segment.extend(payload);
+    segment
+}
+
+#[test]
+fn test_parse_jpeg_xmp() {
+    let exif = rexif::parse_file("./tests/img/jpg/gps/DSCN0010.jpg").unwrap();
+    let xmp = exif.xmp().expect("xmp packet");
+    assert!(xmp.starts_with(b"<?xpacket begin="), "{}", String::from_utf8_lossy(xmp));
+    assert!(exif.extended_xmp().is_none());
+
+    let guid = b"0123456789ABCDEF0123456789ABCDEF";
+    let main = [&b"<x:xmpmeta><rdf:Description xmpNote:HasExtendedXMP=\""[..], guid, b"\"/></x:xmpmeta>"].concat();
+    let extended = b"<x:xmpmeta><rdf:Description photoshop:History=\"long\"/></x:xmpmeta>";
+    let chunk = |offset: usize, data: &[u8]| {
+        jpeg_app1(&[
+            b"http://ns.adobe.com/xmp/extension/\0",
+            guid,
+            &(extended.len() as u32).to_be_bytes(),
+            &(offset as u32).to_be_bytes(),
+            data,
+        ])
+    };
+
+    let original = std::fs::read("./tests/img/profile.jpg").unwrap();
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend(jpeg_app1(&[b"http://ns.adobe.com/xap/1.0/\0", &main]));
+    jpeg.extend(chunk(20, &extended[20..]));
+    jpeg.extend(chunk(0, &extended[..20]));
+    jpeg.extend(&original[2..]);
+
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.xmp(), Some(&main[..]));
+    assert_eq!(exif.extended_xmp(), Some(&extended[..]));
+}
+
+#[test]
+fn test_parse_tiff_xmp() {
+    let xmp = b"<x:xmpmeta/>";
+    let mut tiff = vec![b'I', b'I', 42, 0, 8, 0, 0, 0, 1, 0];
+    tiff.extend([0xbc, 0x02, 1, 0]);
+    tiff.extend((xmp.len() as u32).to_le_bytes());
+    tiff.extend(26u32.to_le_bytes());
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(xmp);
+
+    let exif = parse_buffer(&tiff).unwrap();
+    assert_eq!(exif.mime, "image/tiff");
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+}
+
+#[test]
+fn test_parse_xmp_properties() {
+    let exif = rexif::parse_file("./tests/img/jpg/long_description.jpg").unwrap();
+    let xmp = exif.parse_xmp().expect("xmp");
+    assert_eq!(xmp.get("photoshop:City"), Some(&XmpValue::Simple("KANDAHAR ARMY AIRFIELD".into())));
+    assert_eq!(xmp.get("dc:creator"), Some(&XmpValue::Array(vec!["SSG KYLE DAVIS".into()])));
+    assert_eq!(xmp.get("dc:title").and_then(XmpValue::as_str), Some("030904-A-2140D-006"));
+    assert_eq!(xmp.get_tag(ExifTag::ColorSpace), Some(&XmpValue::Simple("1".into())));
+
+    let packet = br#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+        <rdf:Description rdf:about="" xmlns:ex="http://ns.adobe.com/exif/1.0/" xmlns:dc="http://purl.org/dc/elements/1.1/"
+            ex:FNumber="28/10" ex:DateTimeOriginal="2021-03-04T05:06:07.50+01:00" ex:GPSLatitude="37,46.5N">
+            <ex:ISOSpeedRatings><rdf:Seq><rdf:li>200</rdf:li></rdf:Seq></ex:ISOSpeedRatings>
+            <ex:Flash rdf:parseType="Resource"><ex:Fired>True</ex:Fired></ex:Flash>
+            <dc:subject><rdf:Bag><rdf:li>Fish &amp; Chips</rdf:li><rdf:li>&#x263A;</rdf:li></rdf:Bag></dc:subject>
+        </rdf:Description></rdf:RDF></x:xmpmeta>"#;
+    let xmp = Xmp::parse(packet);
+    let fnumber = xmp.properties.iter().find(|p| p.name == "FNumber").unwrap();
+    assert_eq!(fnumber.prefix, "exif");
+    assert_eq!(fnumber.tag, Some(ExifTag::FNumber));
+    assert_eq!(xmp.get("dc:subject"), Some(&XmpValue::Array(vec!["Fish & Chips".into(), "\u{263a}".into()])));
+    assert!(xmp.get("exif:Flash").is_none());
+    assert!(xmp.get("exif:Fired").is_none());
+
+    // a TIFF with the packet in its XMP tag
+    let mut tiff = ExifData::new("image/tiff", vec![], false);
+    tiff.set(ExifTag::XMP, TagValue::U8(packet.to_vec())).unwrap();
+    let exif = parse_buffer(&tiff.serialize().unwrap()).unwrap();
+    assert_eq!(exif.xmp(), Some(&packet[..]));
+    assert_eq!(exif.effective_value(ExifTag::FNumber).as_deref(), Some(&TagValue::URational(vec![URational { numerator: 28, denominator: 10 }])));
+    assert_eq!(exif.effective_value(ExifTag::ISOSpeedRatings).as_deref(), Some(&TagValue::U16(vec![200])));
+    assert_eq!(exif.effective_value(ExifTag::DateTimeOriginal).as_deref(), Some(&TagValue::Ascii("2021:03:04 05:06:07".into())));
+    assert_eq!(exif.effective_value(ExifTag::GPSLatitudeRef).as_deref(), Some(&TagValue::Ascii("N".into())));
+    assert_eq!(exif.effective_value(ExifTag::GPSLatitude).as_deref(), Some(&TagValue::URational(vec![
+        URational { numerator: 37, denominator: 1 },
+        URational { numerator: 465, denominator: 10 },
+        URational { numerator: 0, denominator: 1 },
+    ])));
+    assert!(exif.effective_value(ExifTag::Model).is_none());
+
+    // EXIF takes precedence over xmp:ModifyDate, which is 2008-05-10T20:55:15
+    let exif = rexif::parse_file("./tests/img/jpg/Pentax_K10D.jpg").unwrap();
+    assert_eq!(exif.effective_value(ExifTag::DateTime).as_deref(), Some(&TagValue::Ascii("2008:07:31 15:56:49".into())));
+
+    // the thumbnail's resolution in IFD1 isn't the image's
+    let mut exif = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    assert!(exif.entries.iter().any(|e| e.kind == IfdKind::Ifd1 && e.tag == ExifTag::XResolution));
+    exif.remove(ExifTag::XResolution);
+    assert!(exif.effective_value(ExifTag::XResolution).is_none());
+}
+
+fn iptc_dataset(record: u8, dataset: u8, value: &[u8]) -> Vec<u8> {
+    [&[0x1c, record, dataset][..], &(value.len() as u16).to_be_bytes(), value].concat()
+}
+
+#[test]
+fn test_parse_iptc() {
+    let iptc = [
+        iptc_dataset(1, 90, b"\x1b%G"),
+        iptc_dataset(2, 0, &[0, 4]),
+        iptc_dataset(2, 105, "Caf\u{e9} opens".as_bytes()),
+        iptc_dataset(2, 25, b"food"),
+        iptc_dataset(2, 25, b"news"),
+        iptc_dataset(2, 80, b"Jane Doe"),
+        iptc_dataset(2, 90, b"Paris"),
+        iptc_dataset(2, 55, b"20240131"),
+        iptc_dataset(2, 60, b"101500-0130"),
+        iptc_dataset(2, 200, b"?"),
+    ].concat();
+
+    let mut resources = b"8BIM\x03\xed\x03abc\0\0\0\x02\0\0".to_vec();
+    resources.extend(b"8BIM\x04\x04\0\0");
+    resources.extend((iptc.len() as u32).to_be_bytes());
+    resources.extend(&iptc);
+    let payload = [&b"Photoshop 3.0\0"[..], &resources].concat();
+
+    let original = std::fs::read("./tests/img/profile.jpg").unwrap();
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend([0xff, 0xed]);
+    jpeg.extend((payload.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(payload);
+    jpeg.extend(&original[2..]);
+
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.iptc().len(), 10);
+    assert_eq!(exif.iptc_values(IptcTag::RecordVersion).next(), Some(&IptcValue::Number(4)));
+    assert_eq!(exif.iptc_values(IptcTag::Headline).next(), Some(&IptcValue::Text("Caf\u{e9} opens".into())));
+    let keywords: Vec<_> = exif.iptc_values(IptcTag::Keywords).map(|k| k.to_string()).collect();
+    assert_eq!(keywords, ["food", "news"]);
+    assert_eq!(exif.iptc_values(IptcTag::ByLine).next(), Some(&IptcValue::Text("Jane Doe".into())));
+    assert_eq!(exif.iptc_values(IptcTag::City).next(), Some(&IptcValue::Text("Paris".into())));
+    assert_eq!(exif.iptc_values(IptcTag::DateCreated).next(), Some(&IptcValue::Date { year: 2024, month: 1, day: 31 }));
+    let time = exif.iptc_values(IptcTag::TimeCreated).next().unwrap();
+    assert_eq!(time, &IptcValue::Time { hour: 10, minute: 15, second: 0, utc_offset: -90 });
+    assert_eq!(time.to_string(), "10:15:00-01:30");
+    let unknown = exif.iptc().last().unwrap();
+    assert_eq!((unknown.tag, unknown.record, unknown.dataset), (IptcTag::UnknownToMe, 2, 200));
+
+    // Without CodedCharacterSet, text that isn't UTF-8 is Latin-1
+    let iptc = iptc_dataset(2, 120, b"Caf\xe9");
+    let entries = [b"8BIM\x04\x04\0\0", &(iptc.len() as u32).to_be_bytes()[..], &iptc].concat();
+    let payload = [&b"Photoshop 3.0\0"[..], &entries].concat();
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend([0xff, 0xed]);
+    jpeg.extend((payload.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(&payload);
+    jpeg.extend(&original[2..]);
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.iptc_values(IptcTag::Caption).next(), Some(&IptcValue::Text("Caf\u{e9}".into())));
+
+    // IPTC without EXIF
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend([0xff, 0xed]);
+    jpeg.extend((payload.len() as u16 + 2).to_be_bytes());
+    jpeg.extend(&payload);
+    let scan = [0xff, 0xda, 0, 2, 0, 0, 0, 0, 0xff, 0xd9];
+    jpeg.extend(scan);
+    assert!(matches!(parse_buffer(&[&jpeg[..2], &scan].concat()), Err(ExifError::JpegWithoutExif(_))));
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert!(exif.entries.is_empty());
+    assert_eq!(exif.iptc_values(IptcTag::Caption).next(), Some(&IptcValue::Text("Caf\u{e9}".into())));
+}
+
+#[test]
+fn test_parse_icc_profile() {
+    let exif = rexif::parse_file("./tests/img/profile.jpg").unwrap();
+    let header = exif.icc_header().expect("icc header");
+    assert_eq!(header.version, (4, 0, 0));
+    assert_eq!((&header.class[..], &header.color_space[..], &header.pcs[..]), ("mntr", "RGB", "XYZ"));
+    assert_eq!(header.description.as_deref(), Some("Display P3"));
+
+    let exif = rexif::parse_file("./tests/img/tiff/BSG1.tiff").unwrap();
+    let header = exif.icc_header().expect("icc header");
+    assert_eq!(header.version, (2, 0, 0));
+    assert_eq!(header.description.as_deref(), Some("Color LCD"));
+
+    // Profile split into two APP2 segments, in the wrong order
+    let profile = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap().icc_profile().unwrap().to_vec();
+    let chunk = |seq: u8, data: &[u8]| {
+        let payload = [&b"ICC_PROFILE\0"[..], &[seq, 2], data].concat();
+        [&[0xff, 0xe2][..], &(payload.len() as u16 + 2).to_be_bytes(), &payload].concat()
+    };
+    let original = std::fs::read("./tests/img/jpg/gps/DSCN0010.jpg").unwrap();
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend(chunk(2, &profile[1000..]));
+    jpeg.extend(chunk(1, &profile[..1000]));
+    jpeg.extend(&original[2..]);
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert_eq!(exif.icc_profile(), Some(&profile[..]));
+    assert_eq!(exif.icc_header().unwrap().description.as_deref(), Some("sRGB IEC61966-2.1"));
+
+    // A tag count larger than the tag table
+    let truncated = [&profile[..128], &u32::MAX.to_be_bytes()[..], &profile[132..144]].concat();
+    let header = IccHeader::parse(&truncated).unwrap();
+    assert_eq!(header.color_space, "RGB");
+    assert!(header.description.is_none());
+
+    // A missing chunk
+    let mut jpeg = original[..2].to_vec();
+    jpeg.extend(chunk(2, &profile[1000..]));
+    jpeg.extend(&original[2..]);
+    let (exif, warnings) = parse_buffer_quiet(&jpeg);
+    assert!(exif.unwrap().icc_profile().is_none());
+    assert_eq!(warnings.len(), 1, "{warnings:?}");
+}
+
+fn png_with_chunks(chunks: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    let ihdr: &[u8] = &[0, 0, 0, 1, 0, 0, 0, 1, 8, 2, 0, 0, 0];
+    for (kind, data) in [(&b"IHDR"[..], ihdr)].iter().chain(chunks).chain(&[(&b"IEND"[..], &b""[..])]) {
+        png.extend((data.len() as u32).to_be_bytes());
+        png.extend(*kind);
+        png.extend(*data);
+        // CRC isn't checked
+        png.extend([0, 0, 0, 0]);
+    }
+    png
+}
+
+#[test]
+fn test_parse_png_exif() {
+    let expected = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    // serialized EXIF starts with the "Exif\0\0" header
+    let prefixed = expected.serialize().unwrap();
+    let tiff = &prefixed[6..];
+
+    let exif = parse_buffer(&png_with_chunks(&[(b"eXIf", tiff)])).unwrap();
+    assert_eq!(exif.mime, "image/png");
+    assert_eq!(exif.entries, expected.entries);
+
+    let exif = parse_buffer(&png_with_chunks(&[(b"eXIf", &prefixed)])).unwrap();
+    assert_eq!(exif.entries, expected.entries);
+
+    // ImageMagick's hex-encoded profile in a compressed text chunk
+    let hex: String = tiff.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>()
+        .chunks(36).map(|line| line.concat() + "\n").collect();
+    let profile = format!("\nexif\n{:8}\n{hex}", tiff.len());
+    let ztxt = [&b"Raw profile type exif\0\0"[..], &miniz_oxide::deflate::compress_to_vec_zlib(profile.as_bytes(), 6)].concat();
+    let xmp = b"<x:xmpmeta xmlns:x='adobe:ns:meta/'/>";
+    let itxt = [&b"XML:com.adobe.xmp\0\0\0\0\0"[..], xmp].concat();
+    let exif = parse_buffer(&png_with_chunks(&[(b"iTXt", &itxt), (b"zTXt", &ztxt)])).unwrap();
+    assert_eq!(exif.entries, expected.entries);
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+
+    let tiff = profile.as_bytes();
+    let exif = parse_buffer(&png_with_chunks(&[(b"tEXt", &[&b"Raw profile type exif\0"[..], tiff].concat())])).unwrap();
+    assert_eq!(exif.entries, expected.entries);
+
+    // a length that overflows when doubled
+    let profile = format!("\nexif\n{}\n{hex}", usize::MAX);
+    let exif = parse_buffer_quiet(&png_with_chunks(&[(b"tEXt", &[&b"Raw profile type exif\0"[..], profile.as_bytes()].concat())])).0;
+    assert!(exif.is_err());
+
+    let exif = parse_buffer(&png_with_chunks(&[]));
+    assert!(matches!(exif, Err(ExifError::ImageWithoutExif(_))), "{exif:?}");
+}
+
+fn webp_with_chunks(chunks: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+    for (fourcc, data) in chunks {
+        webp.extend(*fourcc);
+        webp.extend((data.len() as u32).to_le_bytes());
+        webp.extend(*data);
+        if data.len() % 2 == 1 {
+            webp.push(0);
+        }
+    }
+    let size = (webp.len() as u32 - 8).to_le_bytes();
+    webp[4..8].copy_from_slice(&size);
+    webp
+}
+
+fn webp_chunk_names(webp: &[u8]) -> Vec<String> {
+    let mut names = vec![];
+    let mut pos = 12;
+    while pos + 8 <= webp.len() {
+        names.push(String::from_utf8_lossy(&webp[pos..pos + 4]).into_owned());
+        let size = u32::from_le_bytes(webp[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        pos += 8 + size + size % 2;
+    }
+    names
+}
+
+#[test]
+fn test_webp_exif() {
+    let expected = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    let serialized = expected.serialize().unwrap();
+
+    let xmp = b"<x:xmpmeta xmlns:x='adobe:ns:meta/'/>";
+    let webp = webp_with_chunks(&[(b"VP8X", &[0x0c, 0, 0, 0, 9, 0, 0, 4, 0, 0]), (b"VP8L", &[0x2f, 9, 0, 1, 0x10, 0]), (b"EXIF", &serialized), (b"XMP ", xmp)]);
+    let exif = parse_buffer(&webp).unwrap();
+    assert_eq!(exif.mime, "image/webp");
+    assert_eq!(exif.entries, expected.entries);
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+
+    // Simple format is converted to VP8X, with the size and alpha from the VP8L header
+    let simple = webp_with_chunks(&[(b"VP8L", &[0x2f, 9, 0, 1, 0x10, 0])]);
+    assert!(matches!(parse_buffer(&simple), Err(ExifError::ImageWithoutExif(_))));
+    let written = write_exif_to_webp(&simple, &expected).unwrap();
+    assert_eq!(webp_chunk_names(&written), ["VP8X", "VP8L", "EXIF"]);
+    assert_eq!(&written[20..30], &[0x18, 0, 0, 0, 9, 0, 0, 4, 0, 0]);
+    assert_eq!(u32::from_le_bytes(written[4..8].try_into().unwrap()) as usize, written.len() - 8);
+    assert_eq!(parse_buffer(&written).unwrap().entries, expected.entries);
+
+    // Existing EXIF is replaced, and goes before XMP
+    let other = rexif::parse_file("./tests/img/jpg/Nikon_D70.jpg").unwrap();
+    let written = write_exif_to_webp(&webp, &other).unwrap();
+    assert_eq!(webp_chunk_names(&written), ["VP8X", "VP8L", "EXIF", "XMP "]);
+    assert_eq!(written[20], 0x0c | 0x08);
+    let exif = parse_buffer(&written).unwrap();
+    assert_eq!(exif.entries, other.entries);
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+
+    // EXIF goes after the image data, even if an unknown chunk comes first
+    let vp8x: &[u8] = &[0x20, 0, 0, 0, 9, 0, 0, 4, 0, 0];
+    let webp = webp_with_chunks(&[(b"VP8X", vp8x), (b"ICCP", b"icc"), (b"abcd", b"x"), (b"VP8L", &[0x2f, 9, 0, 1, 0x10, 0])]);
+    let written = write_exif_to_webp(&webp, &expected).unwrap();
+    assert_eq!(webp_chunk_names(&written), ["VP8X", "ICCP", "abcd", "VP8L", "EXIF"]);
+    let webp = webp_with_chunks(&[(b"VP8X", vp8x), (b"ANIM", &[0; 6]), (b"abcd", b"x"), (b"ANMF", b"frame1"), (b"ANMF", b"frame2"), (b"efgh", b"y")]);
+    let written = write_exif_to_webp(&webp, &expected).unwrap();
+    assert_eq!(webp_chunk_names(&written), ["VP8X", "ANIM", "abcd", "ANMF", "ANMF", "EXIF", "efgh"]);
+}
+
+fn iso_box(kind: &[u8], data: &[u8]) -> Vec<u8> {
+    [&(data.len() as u32 + 8).to_be_bytes()[..], kind, data].concat()
+}
+
+/// HEIF with the Exif item in `idat`, and the XMP item in `mdat`
+fn heif_with_items(brands: &[u8], exif: &[u8], xmp: &[u8]) -> Vec<u8> {
+    let ftyp = iso_box(b"ftyp", &[&brands[..4], &[0, 0, 0, 0], brands].concat());
+    let iinf = iso_box(b"iinf", &[
+        &[0, 0, 0, 0, 0, 2][..],
+        &iso_box(b"infe", b"\x02\0\0\0\0\x01\0\0Exif\0"),
+        &iso_box(b"infe", b"\x02\0\0\0\0\x02\0\0mimeXMP\0application/rdf+xml\0"),
+    ].concat());
+    let meta = |mdat_offset: u32| {
+        // version 1, 4-byte offsets and lengths, no base offset, two items with one extent
+        let mut iloc = vec![1, 0, 0, 0, 0x44, 0, 0, 2];
+        iloc.extend([0, 1, 0, 1, 0, 0, 0, 1]);
+        iloc.extend([0, 0, 0, 0].iter().chain(&(exif.len() as u32).to_be_bytes()));
+        iloc.extend([0, 2, 0, 0, 0, 0, 0, 1]);
+        iloc.extend(mdat_offset.to_be_bytes().iter().chain(&(xmp.len() as u32).to_be_bytes()));
+        iso_box(b"meta", &[&[0, 0, 0, 0][..], &iinf, &iso_box(b"iloc", &iloc), &iso_box(b"idat", exif)].concat())
+    };
+    let mdat_offset = ftyp.len() + meta(0).len() + 8;
+    [ftyp, meta(mdat_offset as u32), iso_box(b"mdat", xmp)].concat()
+}
+
+#[test]
+fn test_parse_heif_exif() {
+    let expected = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    // the item starts with the offset of the TIFF header, after "Exif\0\0"
+    let item = [&[0, 0, 0, 6][..], &expected.serialize().unwrap()].concat();
+    let xmp = b"<x:xmpmeta xmlns:x='adobe:ns:meta/'/>";
+
+    let exif = parse_buffer(&heif_with_items(b"heicmif1", &item, xmp)).unwrap();
+    assert_eq!(exif.mime, "image/heic");
+    assert_eq!(exif.entries, expected.entries);
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+
+    let exif = parse_buffer(&heif_with_items(b"mif1avif", &item, xmp)).unwrap();
+    assert_eq!(exif.mime, "image/avif");
+    assert_eq!(exif.entries, expected.entries);
+
+    // XMP without EXIF
+    let exif = parse_buffer(&heif_with_items(b"mif1", &item[..8], xmp)).unwrap();
+    assert!(exif.entries.is_empty());
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+}
+
+#[test]
+fn test_parse_jxl_exif() {
+    let expected = rexif::parse_file("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    let exif_box = iso_box(b"Exif", &[&[0, 0, 0, 6][..], &expected.serialize().unwrap()].concat());
+    let xmp = b"<x:xmpmeta xmlns:x='adobe:ns:meta/'/>";
+    let signature = b"\0\0\0\x0cJXL \r\n\x87\n";
+    let ftyp = iso_box(b"ftyp", b"jxl \0\0\0\0jxl ");
+    let codestream = iso_box(b"jxlc", b"\xff\x0a");
+
+    let jxl = [&signature[..], &ftyp, &exif_box, &iso_box(b"xml ", xmp), &codestream].concat();
+    let exif = parse_buffer(&jxl).unwrap();
+    assert_eq!(exif.mime, "image/jxl");
+    assert_eq!(exif.entries, expected.entries);
+    assert_eq!(exif.xmp(), Some(&xmp[..]));
+
+    // Brotli stream with a single uncompressed meta-block
+    let mut brotli = (((xmp.len() as u32 - 1) << 4) | (1 << 20)).to_le_bytes()[..3].to_vec();
+    brotli.extend(xmp);
+    brotli.push(0x03);
+    let brob = iso_box(b"brob", &[&b"xml "[..], &brotli].concat());
+    let jxl = [&signature[..], &ftyp, &exif_box, &brob, &codestream].concat();
+    let (exif, warnings) = parse_buffer_quiet(&jxl);
+    if cfg!(feature = "brotli") {
+        assert_eq!(exif.unwrap().xmp(), Some(&xmp[..]));
+        assert!(warnings.is_empty(), "{warnings:?}");
+    } else {
+        assert_eq!(exif.unwrap().xmp(), None);
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+    }
+
+    let exif = parse_buffer(b"\xff\x0a\xfa\x1f\0\0\0\0\0\0\0\0");
+    assert!(matches!(exif, Err(ExifError::ImageWithoutExif(_))), "{exif:?}");
+}
+
+/// Little-endian RAW file with a `Make` in IFD0, and two SubIFDs that link to each other.
+/// The header can be longer than 8 bytes, e.g. for CR2.
+fn raw_with_sub_ifds(header: &[u8], make: &[u8]) -> Vec<u8> {
+    let ifd0 = header.len() as u32;
+    let make_offset = ifd0 + 30;
+    let sub_ifds_offset = make_offset + make.len() as u32 + 1;
+    let (sub_ifd_a, sub_ifd_b) = (sub_ifds_offset + 8, sub_ifds_offset + 38);
+
+    let mut tiff = header.to_vec();
+    tiff[4..8].copy_from_slice(&ifd0.to_le_bytes());
+    tiff.extend([2, 0, 0x0f, 0x01, 2, 0]);
+    tiff.extend((make.len() as u32 + 1).to_le_bytes());
+    tiff.extend(make_offset.to_le_bytes());
+    tiff.extend([0x4a, 0x01, 4, 0, 2, 0, 0, 0]);
+    tiff.extend(sub_ifds_offset.to_le_bytes());
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(make);
+    tiff.push(0);
+    tiff.extend(sub_ifd_a.to_le_bytes());
+    tiff.extend(sub_ifd_b.to_le_bytes());
+    for (width, next) in [(6000u32, sub_ifd_b), (160, sub_ifd_a)] {
+        tiff.extend([2, 0, 0x00, 0x01, 4, 0, 1, 0, 0, 0]);
+        tiff.extend(width.to_le_bytes());
+        tiff.extend([0x4a, 0x01, 4, 0, 1, 0, 0, 0]);
+        tiff.extend(next.to_le_bytes());
+        tiff.extend([0, 0, 0, 0]);
+    }
+    tiff
+}
+
+#[test]
+fn test_parse_raw_sub_ifds() {
+    let nef = raw_with_sub_ifds(b"II*\0\0\0\0\0", b"NIKON CORPORATION");
+    let exif = parse_buffer(&nef).unwrap();
+    assert_eq!(exif.mime, "image/x-nikon-nef");
+    let widths: Vec<_> = exif.entries.iter()
+        .filter(|e| e.kind == IfdKind::SubIfd && e.ifd.tag == 0x0100)
+        .map(|e| e.value.to_i64(0).unwrap())
+        .collect();
+    assert_eq!(widths, [160, 6000]);
+
+    // SubIFDs with the IFD field type (13) instead of LONG
+    let mut ifd_type = nef.clone();
+    assert_eq!(&ifd_type[22..26], &[0x4a, 0x01, 4, 0]);
+    ifd_type[24] = 13;
+    let (exif_ifd_type, warnings) = parse_buffer_quiet(&ifd_type);
+    assert_eq!(exif_ifd_type.unwrap().entries, exif.entries);
+    assert!(warnings.is_empty(), "{warnings:?}");
+
+    // SubIFDs aren't serialized, so neither is the link to them
+    let reparsed = parse_buffer(&exif.serialize().unwrap()).unwrap();
+    assert!(reparsed.entries.iter().all(|e| e.kind != IfdKind::SubIfd && e.tag != ExifTag::SubIFDs));
+    assert_eq!(reparsed.entries.len(), exif.entries.len() - 5);
+
+    for (header, mime) in [
+        (&b"IIRO\0\0\0\0"[..], "image/x-olympus-orf"),
+        (b"IIU\0\0\0\0\0", "image/x-panasonic-rw2"),
+        (b"II*\0\0\0\0\0CR\x02\0\0\0\0\0", "image/x-canon-cr2"),
+    ] {
+        let exif = parse_buffer(&raw_with_sub_ifds(header, b"Canon")).unwrap();
+        assert_eq!(exif.mime, mime);
+        assert_eq!(exif.entries.iter().filter(|e| e.kind == IfdKind::SubIfd).count(), 4);
+    }
+    assert_eq!(parse_buffer(&raw_with_sub_ifds(b"II*\0\0\0\0\0", b"SONY")).unwrap().mime, "image/x-sony-arw");
+    assert_eq!(parse_buffer(&raw_with_sub_ifds(b"II*\0\0\0\0\0", b"Canon")).unwrap().mime, "image/tiff");
+}
+
+/// Little-endian TIFF with one IFD of ASCII entries
+fn tiff_with_ascii_entries(entries: &[(u16, &str)]) -> Vec<u8> {
+    let mut data_offset = 8 + 2 + entries.len() as u32 * 12 + 4;
+    let mut tiff = vec![b'I', b'I', 42, 0, 8, 0, 0, 0];
+    tiff.extend((entries.len() as u16).to_le_bytes());
+    let mut data = vec![];
+    for (tag, value) in entries {
+        let value = [value.as_bytes(), &[0]].concat();
+        tiff.extend(tag.to_le_bytes());
+        tiff.extend([2, 0]);
+        tiff.extend((value.len() as u32).to_le_bytes());
+        if value.len() <= 4 {
+            tiff.extend(&value);
+            tiff.extend(vec![0; 4 - value.len()]);
+        } else {
+            tiff.extend(data_offset.to_le_bytes());
+            data_offset += value.len() as u32;
+            data.extend(value);
+        }
+    }
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(data);
+    tiff
+}
+
+#[test]
+fn test_parse_cr3_raf() {
+    let canon_uuid = b"\x85\xc0\xb6\x87\x82\x0f\x11\xe0\x81\x11\xf4\xce\x46\x2b\x6a\x48";
+    let metadata = [
+        &canon_uuid[..],
+        &iso_box(b"CMT1", &tiff_with_ascii_entries(&[(0x010f, "Canon")])),
+        &iso_box(b"CMT2", &tiff_with_ascii_entries(&[(0x9003, "2024:01:02 03:04:05")])),
+        &iso_box(b"CMT3", &tiff_with_ascii_entries(&[(0x0006, "Canon EOS R5")])),
+        &iso_box(b"CMT4", &tiff_with_ascii_entries(&[(0x0001, "N")])),
+    ].concat();
+    let cr3 = [iso_box(b"ftyp", b"crx \0\0\0\x01crx isom"), iso_box(b"moov", &iso_box(b"uuid", &metadata))].concat();
+
+    let exif = parse_buffer(&cr3).unwrap();
+    assert_eq!(exif.mime, "image/x-canon-cr3");
+    let kinds: Vec<_> = exif.entries.iter().map(|e| (e.tag, e.kind)).collect();
+    assert_eq!(kinds, [
+        (ExifTag::Make, IfdKind::Ifd0),
+        (ExifTag::ExifOffset, IfdKind::Ifd0),
+        (ExifTag::GPSOffset, IfdKind::Ifd0),
+        (ExifTag::DateTimeOriginal, IfdKind::Exif),
+        (ExifTag::CanonImageType, IfdKind::Makernote),
+        (ExifTag::GPSLatitudeRef, IfdKind::Gps),
+    ]);
+    // links to the Exif and GPS IFDs are added, so that they can be serialized
+    let reparsed = parse_buffer(&exif.serialize().unwrap()).unwrap();
+    assert_eq!(reparsed.entries.len(), 5);
+
+    let jpeg = std::fs::read("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    let mut raf = b"FUJIFILMCCD-RAW 0201FF129502X-T5".to_vec();
+    raf.resize(0x54, 0);
+    raf.extend(0x100u32.to_be_bytes());
+    raf.extend((jpeg.len() as u32).to_be_bytes());
+    raf.resize(0x100, 0);
+    raf.extend(&jpeg);
+    let exif = parse_buffer(&raf).unwrap();
+    assert_eq!(exif.mime, "image/x-fuji-raf");
+    assert_eq!(exif.entries, parse_buffer(&jpeg).unwrap().entries);
+}
+
+fn quicktime_text(text: &str) -> Vec<u8> {
+    [&(text.len() as u16).to_be_bytes()[..], &[0x15, 0xc7], text.as_bytes()].concat()
+}
+
+#[test]
+fn test_parse_video_metadata() {
+    // 2020-01-02 03:04:05 UTC, in seconds since 1904
+    let mvhd = iso_box(b"mvhd", &[&[0, 0, 0, 0][..], &3_660_779_045u32.to_be_bytes(), &[0; 8]].concat());
+    let udta = iso_box(b"udta", &[
+        iso_box(b"\xa9xyz", &quicktime_text("+37.3349-122.0090+030.000/")),
+        iso_box(b"\xa9mak", &quicktime_text("Apple")),
+        iso_box(b"\xa9mod", &quicktime_text("iPhone 12")),
+    ].concat());
+    let mp4 = [iso_box(b"ftyp", b"isom\0\0\0\x01isomavc1"), iso_box(b"moov", &[&mvhd[..], &udta].concat())].concat();
+
+    let exif = parse_buffer(&mp4).unwrap();
+    assert_eq!(exif.mime, "video/mp4");
+    let value = |tag| exif.entries.iter().find(|e| e.tag == tag).map(|e| e.value_more_readable.to_string());
+    assert_eq!(value(ExifTag::DateTimeOriginal).as_deref(), Some("2020:01:02 03:04:05"));
+    assert_eq!(value(ExifTag::Make).as_deref(), Some("Apple"));
+    assert_eq!(value(ExifTag::Model).as_deref(), Some("iPhone 12"));
+    assert_eq!(value(ExifTag::GPSLongitudeRef).as_deref(), Some("W"));
+    let latitude = exif.entries.iter().find(|e| e.tag == ExifTag::GPSLatitude).unwrap();
+    assert_eq!(latitude.value, TagValue::URational(vec![
+        URational { numerator: 37, denominator: 1 },
+        URational { numerator: 20, denominator: 1 },
+        URational { numerator: 5640, denominator: 1000 },
+    ]));
+    let altitude = exif.entries.iter().find(|e| e.tag == ExifTag::GPSAltitude).unwrap();
+    assert_eq!(altitude.value, TagValue::URational(vec![URational { numerator: 30000, denominator: 1000 }]));
+    let reparsed = parse_buffer(&exif.serialize().unwrap()).unwrap();
+    assert_eq!(reparsed.entries, exif.entries);
+
+    // no Exif IFD without a creation date
+    let udta = iso_box(b"udta", &iso_box(b"\xa9mak", &quicktime_text("Apple")));
+    let exif = parse_buffer(&[iso_box(b"ftyp", b"isom\0\0\0\x01isomavc1"), iso_box(b"moov", &udta)].concat()).unwrap();
+    assert_eq!(exif.entries.iter().map(|e| e.tag).collect::<Vec<_>>(), [ExifTag::Make]);
+    let reparsed = parse_buffer(&exif.serialize().unwrap()).unwrap();
+    assert_eq!(reparsed.entries, exif.entries);
+
+    // Apple's keys take precedence, and have the local time
+    let keys = [&[0, 0, 0, 0, 0, 0, 0, 2][..],
+        &iso_box(b"mdta", b"com.apple.quicktime.creationdate"),
+        &iso_box(b"mdta", b"com.apple.quicktime.location.ISO6709"),
+    ].concat();
+    let item = |index: u32, value: &str| iso_box(&index.to_be_bytes(), &iso_box(b"data", &[&[0, 0, 0, 1, 0, 0, 0, 0][..], value.as_bytes()].concat()));
+    let ilst = [item(1, "2020-01-02T04:04:05+0100"), item(2, "-3351-07017/")].concat();
+    let meta = [iso_box(b"hdlr", &[0; 24]), iso_box(b"keys", &keys), iso_box(b"ilst", &ilst)].concat();
+    let mov = [iso_box(b"ftyp", b"qt  \0\0\0\0qt  "), iso_box(b"moov", &[&mvhd[..], &udta, &iso_box(b"meta", &meta)].concat())].concat();
+
+    let exif = parse_buffer(&mov).unwrap();
+    assert_eq!(exif.mime, "video/quicktime");
+    let value = |tag| exif.entries.iter().find(|e| e.tag == tag).map(|e| e.value_more_readable.to_string());
+    assert_eq!(value(ExifTag::DateTimeOriginal).as_deref(), Some("2020:01:02 04:04:05"));
+    assert_eq!(value(ExifTag::GPSLatitudeRef).as_deref(), Some("S"));
+    assert_eq!(value(ExifTag::GPSAltitude), None);
+    let longitude = exif.entries.iter().find(|e| e.tag == ExifTag::GPSLongitude).unwrap();
+    assert_eq!(longitude.value, TagValue::URational(vec![
+        URational { numerator: 70, denominator: 1 },
+        URational { numerator: 17, denominator: 1 },
+        URational { numerator: 0, denominator: 1000 },
+    ]));
+}
+
+/// MPO file with an MPF segment right after SOI, followed by the primary and the secondary image
+fn mpo_file(primary: &[u8], secondary: &[u8]) -> Vec<u8> {
+    // MP Index IFD with the version, number of images, and two MP entries
+    let mpf_len = 4 + 50 + 32;
+    let primary_len = (primary.len() + 4 + mpf_len) as u32;
+    let tiff_start = 2 + 4 + 4;
+    let mut mpf = b"MPF\0MM\0\x2a\0\0\0\x08\0\x03".to_vec();
+    mpf.extend([0xb0, 0x00, 0, 7, 0, 0, 0, 4, b'0', b'1', b'0', b'0']);
+    mpf.extend([0xb0, 0x01, 0, 4, 0, 0, 0, 1, 0, 0, 0, 2]);
+    mpf.extend([0xb0, 0x02, 0, 7, 0, 0, 0, 32, 0, 0, 0, 50]);
+    mpf.extend([0, 0, 0, 0]);
+    for (attributes, size, offset) in [(0x2003_0000u32, primary_len, 0), (0x0002_0002, secondary.len() as u32, primary_len - tiff_start)] {
+        mpf.extend(attributes.to_be_bytes());
+        mpf.extend(size.to_be_bytes());
+        mpf.extend(offset.to_be_bytes());
+        mpf.extend([0, 0, 0, 0]);
+    }
+    assert_eq!(mpf.len(), mpf_len);
+    [&primary[..2], &[0xff, 0xe2], &(mpf.len() as u16 + 2).to_be_bytes(), &mpf, &primary[2..], secondary].concat()
+}
+
+#[test]
+fn test_parse_mpf_images() {
+    let primary = std::fs::read("./tests/img/jpg/Canon_40D.jpg").unwrap();
+    let secondary = std::fs::read("./tests/img/jpg/Nikon_D70.jpg").unwrap();
+    let mpo = mpo_file(&primary, &secondary);
+    let primary_len = mpo.len() - secondary.len();
+
+    let (exif, warnings) = parse_buffer_quiet(&mpo);
+    let exif = exif.unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    assert_eq!(exif.entries, parse_buffer(&primary).unwrap().entries);
+    assert_eq!(exif.mpf_images().len(), 2);
+    assert_eq!(exif.mpf_images()[0].image_type, MpfImageType::Primary);
+    assert_eq!((exif.mpf_images()[0].offset, exif.mpf_images()[0].size), (0, primary_len));
+    assert!(exif.mpf_images()[0].exif.is_none());
+
+    let second = &exif.mpf_images()[1];
+    assert_eq!(second.image_type, MpfImageType::Disparity);
+    assert_eq!(&mpo[second.offset..second.offset + second.size], &secondary[..]);
+    assert_eq!(second.exif.as_ref().unwrap().entries, parse_buffer(&secondary).unwrap().entries);
+}
+
+#[test]
+fn test_write_exif_to_jpeg() {
+    let original = std::fs::read("./tests/img/profile.jpg").unwrap();
+    let nikon = rexif::parse_file("./tests/img/jpg/Nikon_D70.jpg").unwrap();
+    let serialized = nikon.serialize().unwrap();
+    let expected = parse_buffer(&[&[0xff, 0xd8][..], &jpeg_app1(&[&serialized])].concat()).unwrap();
+
+    // The Exif segment after APP0 is replaced, and the rest of the file is kept
+    let old_segment_end = 20 + 2 + 0x80;
+    let written = write_exif_to_jpeg(&original, &nikon).unwrap();
+    assert_eq!(&written[..20], &original[..20]);
+    assert_eq!(&written[20..22 + 2 + serialized.len()], &jpeg_app1(&[&serialized])[..]);
+    assert_eq!(&written[22 + 2 + serialized.len()..], &original[old_segment_end..]);
+    assert_eq!(parse_buffer(&written).unwrap().entries, expected.entries);
+
+    // Without EXIF, the segment is inserted after APP0
+    let without_exif = [&original[..20], &original[old_segment_end..]].concat();
+    let mut inserted = Vec::new();
+    write_exif_to_jpeg_into(&without_exif, &nikon, &mut inserted).unwrap();
+    assert_eq!(inserted, written);
+
+    // EXIF from a TIFF gets the JPEG header
+    let tiff = parse_buffer(&serialized[6..]).unwrap();
+    assert_eq!(tiff.mime, "image/tiff");
+    assert_eq!(parse_buffer(&write_exif_to_jpeg(&original, &tiff).unwrap()).unwrap().entries, expected.entries);
+
+    // XMP tag that doesn't fit in a JPEG segment
+    let xmp = vec![b' '; 70000];
+    let mut tiff = vec![b'I', b'I', 42, 0, 8, 0, 0, 0, 1, 0, 0xbc, 0x02, 1, 0];
+    tiff.extend((xmp.len() as u32).to_le_bytes());
+    tiff.extend(26u32.to_le_bytes());
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(&xmp);
+    let too_large = write_exif_to_jpeg(&original, &parse_buffer(&tiff).unwrap());
+    assert!(matches!(too_large, Err(ExifError::CannotWrite(_))), "{too_large:?}");
+
+    // Canon's makernote has offsets relative to the TIFF header, which move with the blob
+    // (Canon_40D.jpg has no makernote)
+    let canon_file = std::fs::read("./tests/img/jpg/Canon_DIGITAL_IXUS_400.jpg").unwrap();
+    let mut canon = parse_buffer(&canon_file).unwrap();
+    canon.set(ExifTag::Artist, TagValue::Ascii("Jane Doe".into())).unwrap();
+    let canon_entries = |exif: &ExifData| -> Vec<(u16, TagValue)> {
+        exif.entries.iter().filter(|e| e.namespace == Namespace::Canon).map(|e| (e.ifd.tag, e.value.clone())).collect()
+    };
+    let written = write_exif_to_jpeg(&canon_file, &canon).unwrap();
+    // the parser corrects moved makernotes, so the raw bytes are checked too
+    let fixed = canon.serialize_with(MakernoteOffsets::Fix).unwrap();
+    assert_ne!(fixed, canon.serialize().unwrap());
+    let exif_start = written.windows(EXIF_HEADER.len()).position(|w| w == EXIF_HEADER).unwrap();
+    assert!(written[exif_start..].starts_with(&fixed));
+    let reparsed = parse_buffer(&written).unwrap();
+    assert!(!canon_entries(&canon).is_empty());
+    assert_eq!(canon_entries(&reparsed), canon_entries(&canon));
+}
+
+#[test]
+fn test_write_exif_to_tiff() {
+    let path = Path::new(TIFF_TEST_DIR).join("DudleyLeavittUtah.tiff");
+    let original = std::fs::read(path).unwrap();
+    let mut exif = parse_buffer(&original).unwrap();
+    let tags = |exif: &ExifData| exif.entries.iter().map(|e| (e.kind, e.tag, e.value.to_string())).collect::<Vec<_>>();
+
+    // Removing a tag rewrites IFD-0 at the end, and the image data is kept
+    let removed = exif.entries.iter().position(|e| e.kind == IfdKind::Ifd0 && e.ifd.tag == 0x0131).unwrap();
+    exif.entries.remove(removed);
+    let written = write_exif_to_tiff(&original, &exif).unwrap();
+    assert_eq!(&written[..4], &original[..4]);
+    assert_eq!(&written[8..original.len()], &original[8..]);
+    assert!(u32::from_le_bytes(written[4..8].try_into().unwrap()) as usize >= original.len());
+
+    let rewritten = parse_buffer(&written).unwrap();
+    assert_eq!(rewritten.mime, "image/tiff");
+    assert_eq!(tags(&rewritten), tags(&exif));
+
+    // The byte order of the file can't be changed
+    let little_endian = rexif::parse_file(Path::new(TIFF_TEST_DIR).join("Picoawards.tiff")).unwrap();
+    assert!(little_endian.le);
+    assert!(matches!(write_exif_to_tiff(&original, &little_endian), Err(ExifError::CannotWrite(_))));
+    assert!(matches!(write_exif_to_tiff(b"not a tiff", &exif), Err(ExifError::FileTypeUnknown)));
+}
+
+#[test]
+fn test_set_remove_get() {
+    let mut exif = rexif::parse_file("./tests/img/jpg/Nikon_D70.jpg").unwrap();
+    let has_tag = |exif: &ExifData, tag| exif.entries.iter().any(|e| e.tag == tag);
+    assert!(!exif.entries.iter().any(|e| e.kind == IfdKind::Gps));
+
+    // Replacing a value
+    exif.set(ExifTag::Software, TagValue::Ascii("rexif".into())).unwrap();
+    assert_eq!(exif.get(ExifTag::Software), Some(&TagValue::Ascii("rexif".into())));
+    assert_eq!(exif.entries.iter().filter(|e| e.tag == ExifTag::Software).count(), 1);
+
+    // The GPS IFD is created and linked from IFD0
+    let latitude = vec![URational { numerator: 48, denominator: 1 }, URational { numerator: 51, denominator: 1 }, URational { numerator: 2400, denominator: 100 }];
+    exif.set(ExifTag::GPSLatitudeRef, TagValue::Ascii("N".into())).unwrap();
+    exif.set(ExifTag::GPSLatitude, TagValue::URational(latitude.clone())).unwrap();
+    assert!(has_tag(&exif, ExifTag::GPSOffset));
+    let serialized = parse_buffer(&[&[0xff, 0xd8][..], &jpeg_app1(&[&exif.serialize().unwrap()])].concat()).unwrap();
+    assert_eq!(serialized.get(ExifTag::GPSLatitude), Some(&TagValue::URational(latitude)));
+    assert_eq!(serialized.get(ExifTag::Software), Some(&TagValue::Ascii("rexif".into())));
+
+    // Wrong format or count, and tags that are set when serializing
+    assert!(matches!(exif.set(ExifTag::Orientation, TagValue::U32(vec![1])), Err(ExifError::CannotWrite(_))));
+    assert!(matches!(exif.set(ExifTag::Orientation, TagValue::U16(vec![1, 2])), Err(ExifError::CannotWrite(_))));
+    assert!(matches!(exif.set(ExifTag::ExifOffset, TagValue::U32(vec![0])), Err(ExifError::CannotWrite(_))));
+    assert!(matches!(exif.set(ExifTag::UnknownToMe, TagValue::U8(vec![0])), Err(ExifError::CannotWrite(_))));
+
+    // The GPS IFD is unlinked when it becomes empty
+    assert_eq!(exif.remove(ExifTag::GPSLatitudeRef), Some(TagValue::Ascii("N".into())));
+    assert!(has_tag(&exif, ExifTag::GPSOffset));
+    assert!(exif.remove(ExifTag::GPSLatitude).is_some());
+    assert!(!has_tag(&exif, ExifTag::GPSOffset));
+    assert_eq!(exif.remove(ExifTag::GPSLatitude), None);
+    assert_eq!(exif.get(ExifTag::GPSLatitude), None);
+    exif.serialize().unwrap();
+
+    // The Interoperability IFD is linked from a new Exif IFD
+    let mut exif = rexif::parse_file(Path::new(TIFF_TEST_DIR).join("Picoawards.tiff")).unwrap();
+    exif.set(ExifTag::InteroperabilityIndex, TagValue::Ascii("R98".into())).unwrap();
+    assert!(has_tag(&exif, ExifTag::ExifOffset) && has_tag(&exif, ExifTag::InteropOffset));
+    let serialized = parse_buffer(&exif.serialize().unwrap()).unwrap();
+    assert_eq!(serialized.get(ExifTag::InteroperabilityIndex), Some(&TagValue::Ascii("R98".into())));
+    exif.remove(ExifTag::InteroperabilityIndex);
+    assert!(!has_tag(&exif, ExifTag::ExifOffset) && !has_tag(&exif, ExifTag::InteropOffset));
+}
+
+#[test]
+fn test_ifd_entry_from_value() {
+    let ascii = IfdEntry::from_value(0x010f, &TagValue::Ascii("Nikon".into()), true);
+    assert_eq!((ascii.format, ascii.count, &ascii.data[..]), (IfdFormat::Ascii, 6, &b"Nikon\0"[..]));
+    assert_eq!(ascii.ext_data, ascii.data);
+
+    // Short data is padded within the IFD structure
+    let short = IfdEntry::from_value(0x0112, &TagValue::U16(vec![6]), false);
+    assert_eq!((short.format, short.count, &short.data[..]), (IfdFormat::U16, 1, &[0, 6, 0, 0][..]));
+    assert!(short.ext_data.is_empty());
+
+    let rational = IfdEntry::from_value(0x829a, &TagValue::URational(vec![URational { numerator: 1, denominator: 250 }]), true);
+    assert_eq!((rational.format, rational.count, &rational.data[..]), (IfdFormat::URational, 1, &[1, 0, 0, 0, 250, 0, 0, 0][..]));
+
+    let float = IfdEntry::from_value(0x9999, &TagValue::F32(vec![1.5]), false);
+    assert_eq!(&float.data[..], &1.5f32.to_be_bytes()[..]);
+
+    // Every format decodes back to the same value, in both byte orders
+    let values = [
+        TagValue::U8(vec![1, 2, 3, 4, 5]),
+        TagValue::Ascii("text".into()),
+        TagValue::U16(vec![1, 65535]),
+        TagValue::U32(vec![7, 0xdead_beef]),
+        TagValue::URational(vec![URational { numerator: 28, denominator: 10 }]),
+        TagValue::I8(vec![-1, 2]),
+        TagValue::Undefined(b"0230".to_vec(), true),
+        TagValue::I16(vec![-300]),
+        TagValue::I32(vec![-70000, 5]),
+        TagValue::IRational(vec![IRational { numerator: -1, denominator: 3 }, IRational { numerator: 4, denominator: 5 }]),
+        TagValue::F32(vec![0.25, -2.]),
+        TagValue::F64(vec![std::f64::consts::PI]),
+    ];
+    for le in [true, false] {
+        for value in &values {
+            let entry = IfdEntry::from_value(0xffff, value, le);
+            let mut tiff = if le { vec![b'I', b'I', 42, 0, 8, 0, 0, 0] } else { vec![b'M', b'M', 0, 42, 0, 0, 0, 8] };
+            tiff.extend(if le { 1u16.to_le_bytes() } else { 1u16.to_be_bytes() });
+            tiff.extend(if le { entry.tag.to_le_bytes() } else { entry.tag.to_be_bytes() });
+            tiff.extend(if le { (entry.format as u16).to_le_bytes() } else { (entry.format as u16).to_be_bytes() });
+            tiff.extend(if le { entry.count.to_le_bytes() } else { entry.count.to_be_bytes() });
+            tiff.extend(if entry.in_ifd() { entry.data.clone() } else if le { 26u32.to_le_bytes().to_vec() } else { 26u32.to_be_bytes().to_vec() });
+            tiff.extend([0, 0, 0, 0]);
+            tiff.extend(&entry.ext_data);
+
+            let parsed = parse_buffer(&tiff).unwrap();
+            let expected = match value {
+                TagValue::Undefined(data, _) => TagValue::Undefined(data.clone(), le),
+                value => value.clone(),
+            };
+            assert_eq!(parsed.entries[0].value, expected, "{value:?} le={le}");
+        }
+    }
+}
+
+#[test]
+fn test_parse_big_endian_floats() {
+    // IFD0 with a FLOAT within the entry, and a DOUBLE after the IFD
+    let mut tiff = vec![b'M', b'M', 0, 42, 0, 0, 0, 8, 0, 2];
+    tiff.extend([0xff, 0xf0, 0, 11, 0, 0, 0, 1]);
+    tiff.extend((-0.75f32).to_be_bytes());
+    tiff.extend([0xff, 0xf1, 0, 12, 0, 0, 0, 1, 0, 0, 0, 38]);
+    tiff.extend([0, 0, 0, 0]);
+    tiff.extend(std::f64::consts::E.to_be_bytes());
+
+    let exif = parse_buffer(&tiff).unwrap();
+    assert!(!exif.le);
+    assert_eq!(exif.entries[0].value, TagValue::F32(vec![-0.75]));
+    assert_eq!(exif.entries[1].value, TagValue::F64(vec![std::f64::consts::E]));
+}
+
+#[test]
+fn test_scrub() {
+    let original = std::fs::read("./tests/img/jpg/gps/DSCN0010.jpg").unwrap();
+    let comment = [0xff, 0xfe, 0, 6, b'h', b'i', b'!', b'!'];
+    let jpeg = [&original[..2], &comment, &original[2..]].concat();
+    let exif = parse_buffer(&jpeg).unwrap();
+    assert!(exif.get(ExifTag::GPSLatitude).is_some() && exif.get(ExifTag::MakerNote).is_some() && exif.xmp().is_some());
+    let has_tag = |exif: &ExifData, tag| exif.entries.iter().any(|e| e.tag == tag);
+    let has_comment = |jpeg: &[u8]| jpeg.windows(comment.len()).any(|w| w == comment);
+    // entropy-coded data is copied as-is
+    let tail = &original[original.len() - 10000..];
+
+    let stripped = strip_jpeg_metadata(&jpeg, ScrubPolicy::Gps).unwrap();
+    let gps = parse_buffer(&stripped).unwrap();
+    assert!(!gps.entries.iter().any(|e| e.kind == IfdKind::Gps) && !has_tag(&gps, ExifTag::GPSOffset));
+    assert_eq!(gps.get(ExifTag::MakerNote), exif.get(ExifTag::MakerNote));
+    assert!(gps.xmp().is_none() && has_comment(&stripped) && stripped.ends_with(tail));
+    let mut scrubbed = parse_buffer(&jpeg).unwrap();
+    scrubbed.scrub(ScrubPolicy::Gps);
+    assert_eq!(gps.entries.len(), scrubbed.entries.len());
+    assert!(scrubbed.xmp().is_none());
+
+    let stripped = strip_jpeg_metadata(&jpeg, ScrubPolicy::DeviceIdentifiers).unwrap();
+    let device = parse_buffer(&stripped).unwrap();
+    assert!(!has_tag(&device, ExifTag::MakerNote) && device.entries.iter().all(|e| e.kind != IfdKind::Makernote));
+    assert_eq!(device.get(ExifTag::GPSLatitude), exif.get(ExifTag::GPSLatitude));
+    let mut artist = parse_buffer(&jpeg).unwrap();
+    artist.set(ExifTag::Artist, TagValue::Ascii("Jane Doe".into())).unwrap();
+    artist.scrub(ScrubPolicy::DeviceIdentifiers);
+    assert!(!has_tag(&artist, ExifTag::Artist) && has_tag(&artist, ExifTag::Make));
+
+    let stripped = strip_jpeg_metadata(&jpeg, ScrubPolicy::AllButOrientationAndColor).unwrap();
+    let kept = parse_buffer(&stripped).unwrap();
+    let tags: Vec<_> = kept.entries.iter().map(|e| e.tag).collect();
+    assert_eq!(tags, [ExifTag::Orientation, ExifTag::ExifOffset, ExifTag::ColorSpace]);
+    assert!(!has_comment(&stripped) && stripped.ends_with(tail));
+
+    let stripped = strip_jpeg_metadata(&jpeg, ScrubPolicy::All).unwrap();
+    assert!(parse_buffer(&stripped).is_err());
+    assert!(stripped.ends_with(tail));
+
+    // The secondary image of an MPO is stripped too, and the MPF index follows the resized images
+    let secondary = &original;
+    let mpo = mpo_file(&jpeg, secondary);
+    assert!(parse_buffer(&mpo).unwrap().mpf_images()[1].exif.as_ref().unwrap().get(ExifTag::GPSLatitude).is_some());
+    let stripped = strip_jpeg_metadata(&mpo, ScrubPolicy::Gps).unwrap();
+    let (stripped_exif, warnings) = parse_buffer_quiet(&stripped);
+    let stripped_exif = stripped_exif.unwrap();
+    assert!(warnings.is_empty(), "{warnings:?}");
+    let (first, second) = (&stripped_exif.mpf_images()[0], &stripped_exif.mpf_images()[1]);
+    assert_eq!((first.offset, first.size), (0, second.offset));
+    assert_eq!(second.offset + second.size, stripped.len());
+    assert_eq!(&stripped[second.offset..], &strip_jpeg_metadata(secondary, ScrubPolicy::Gps).unwrap()[..]);
+    let second_exif = second.exif.as_ref().unwrap();
+    assert!(second_exif.entries.iter().all(|e| e.kind != IfdKind::Gps) && second_exif.get(ExifTag::Make).is_some());
+
+    // the primary image's size in the MPF index can't shrink below 0
+    let mut bad_size = mpo.clone();
+    bad_size[64..68].copy_from_slice(&1u32.to_be_bytes());
+    assert!(matches!(strip_jpeg_metadata(&bad_size, ScrubPolicy::Gps), Err(ExifError::CannotWrite(_))));
+
+    let mut exif = parse_buffer(&mpo).unwrap();
+    assert!(exif.mpf_images()[1].exif.as_ref().unwrap().get(ExifTag::Make).is_some());
+    exif.scrub(ScrubPolicy::All);
+    assert!(exif.mpf_images()[1].exif.as_ref().unwrap().entries.is_empty());
+}